@@ -4,6 +4,7 @@ use std::{
     cmp::Ordering,
     collections::{BTreeMap, BTreeSet, HashMap},
     iter,
+    ops::Range,
     rc::Rc,
 };
 
@@ -11,9 +12,11 @@ use crate::{
     error::RuntimeResult,
     eval::{replicator_num, rt_error},
     format::{Format, Formatter},
+    io::LazyStdin,
     lex::Span,
     num::Num,
     pervade::LazyPervade,
+    random,
     rcview::{RcView, RcViewIntoIter},
     runtime::Runtime,
     value::{Atom, Val},
@@ -25,6 +28,7 @@ type Items = RcView<Val>;
 pub enum Array {
     Concrete(Items),
     AsciiString(Rc<str>),
+    Utf32String(Rc<[char]>),
     Cached(Rc<CachedArray>),
     Reverse(Box<Self>),
     Range(Num),
@@ -41,6 +45,10 @@ pub enum Array {
     Scan(Rc<LazyScan>),
     Table(Rc<LazyTable>),
     Classify(Rc<LazyClassify>),
+    Random(Rc<LazyRandom>),
+    Stdin(Rc<LazyStdin>),
+    Transpose(Rc<LazyTranspose>),
+    Column(Rc<LazyColumn>),
 }
 
 fn _array_size() {
@@ -51,6 +59,26 @@ fn _array_size() {
     let _: [u8; 40] = unsafe { transmute(Array::string("")) };
 }
 
+/// The range of the items kept when taking `n` of `len` items
+fn take_range(len: usize, n: i64) -> Range<usize> {
+    let n_abs = (n.unsigned_abs() as usize).min(len);
+    if n >= 0 {
+        0..n_abs
+    } else {
+        len - n_abs..len
+    }
+}
+
+/// The range of the items kept when dropping `n` of `len` items
+fn drop_range(len: usize, n: i64) -> Range<usize> {
+    let n_abs = (n.unsigned_abs() as usize).min(len);
+    if n >= 0 {
+        n_abs..len
+    } else {
+        0..len - n_abs
+    }
+}
+
 fn min_len(a: Option<usize>, b: Option<usize>) -> Option<usize> {
     Some(match (a, b) {
         (Some(a), Some(b)) => a.min(b),
@@ -72,7 +100,51 @@ impl Array {
         if s.is_ascii() {
             Array::AsciiString(s)
         } else {
-            Array::concrete(s.chars())
+            Array::Utf32String(s.chars().collect())
+        }
+    }
+    /// Take items, keeping strings as strings
+    pub fn take(self, n: i64) -> Self {
+        match self {
+            Array::AsciiString(s) => Array::AsciiString(s[take_range(s.len(), n)].into()),
+            Array::Utf32String(s) => Array::Utf32String(s[take_range(s.len(), n)].into()),
+            arr => Array::Take(arr.into(), n),
+        }
+    }
+    /// Drop items, keeping strings as strings
+    pub fn drop(self, n: i64) -> Self {
+        match self {
+            Array::AsciiString(s) => Array::AsciiString(s[drop_range(s.len(), n)].into()),
+            Array::Utf32String(s) => Array::Utf32String(s[drop_range(s.len(), n)].into()),
+            arr => Array::Drop(arr.into(), n),
+        }
+    }
+    /// Reverse items, keeping strings as strings
+    pub fn reverse(self) -> Self {
+        match self {
+            Array::AsciiString(s) => Array::AsciiString(s.chars().rev().collect::<String>().into()),
+            Array::Utf32String(s) => Array::Utf32String(s.iter().rev().copied().collect()),
+            arr => Array::Reverse(arr.into()),
+        }
+    }
+    /// Join two arrays, keeping strings as strings
+    pub fn join_to(self, other: Self) -> Self {
+        match (self, other) {
+            (Array::AsciiString(a), Array::AsciiString(b)) => {
+                Array::AsciiString(format!("{}{}", a, b).into())
+            }
+            (
+                a @ (Array::AsciiString(_) | Array::Utf32String(_)),
+                b @ (Array::AsciiString(_) | Array::Utf32String(_)),
+            ) => Array::Utf32String(a.string_chars().chain(b.string_chars()).collect()),
+            (a, b) => Array::JoinTo(a.into(), b.into()),
+        }
+    }
+    fn string_chars(&self) -> Box<dyn Iterator<Item = char> + '_> {
+        match self {
+            Array::AsciiString(s) => Box::new(s.chars()),
+            Array::Utf32String(s) => Box::new(s.iter().copied()),
+            _ => Box::new(iter::empty()),
         }
     }
     pub fn try_concrete<I>(items: I) -> RuntimeResult<Array>
@@ -117,6 +189,7 @@ impl Array {
         Some(match self {
             Array::Concrete(items) => items.len(),
             Array::AsciiString(s) => s.len(),
+            Array::Utf32String(s) => s.len(),
             Array::Cached(arr) => arr.len()?,
             Array::Reverse(arr) => arr.len()?,
             Array::Range(n) => {
@@ -159,6 +232,10 @@ impl Array {
             Array::Table(table) => table.len()?,
             Array::Classify(_) => return None,
             Array::Deduplicate(_) => return None,
+            Array::Random(_) => return None,
+            Array::Stdin(_) => return None,
+            Array::Transpose(trans) => trans.len()?,
+            Array::Column(col) => col.arr.len()?,
         })
     }
     pub fn get(&self, index: usize) -> RuntimeResult<Option<Cow<Val>>> {
@@ -171,6 +248,7 @@ impl Array {
                 .map(char::from)
                 .map(Val::from)
                 .map(Cow::Owned),
+            Array::Utf32String(s) => s.get(index).copied().map(Val::from).map(Cow::Owned),
             Array::Cached(arr) => arr.get(index)?.map(Cow::Owned),
             Array::Reverse(arr) => {
                 if let Some(len) = arr.len() {
@@ -210,8 +288,7 @@ impl Array {
                         None
                     }
                 } else if let Some(len) = arr.len() {
-                    let n = n.abs() as usize;
-                    arr.get(len - n + index)?
+                    arr.get_in(take_range(len, *n), index)?
                 } else {
                     None
                 }
@@ -221,12 +298,7 @@ impl Array {
                     let n = *n as usize;
                     arr.get(index + n)?
                 } else if let Some(len) = arr.len() {
-                    let n = n.abs() as usize;
-                    if n >= len {
-                        None
-                    } else {
-                        arr.get(len + index - n)?
-                    }
+                    arr.get_in(drop_range(len, *n), index)?
                 } else {
                     None
                 }
@@ -276,8 +348,20 @@ impl Array {
             Array::Table(table) => table.get(index)?.map(Cow::Owned),
             Array::Classify(class) => class.get(index)?.map(Cow::Owned),
             Array::Deduplicate(dedup) => dedup.get(index)?.map(Cow::Owned),
+            Array::Random(rand) => Some(Cow::Owned(rand.get(index).into())),
+            Array::Stdin(stdin) => stdin.get(index)?.map(Cow::Owned),
+            Array::Transpose(trans) => trans.get(index).map(Cow::Owned),
+            Array::Column(col) => col.get(index)?.map(Cow::Owned),
         })
     }
+    /// Get an item of a range of this array
+    fn get_in(&self, range: Range<usize>, index: usize) -> RuntimeResult<Option<Cow<Val>>> {
+        if index < range.len() {
+            self.get(range.start + index)
+        } else {
+            Ok(None)
+        }
+    }
     pub fn iter(&self) -> impl Iterator<Item = RuntimeResult<Cow<Val>>> {
         let mut i = 0;
         iter::from_fn(move || {
@@ -895,3 +979,112 @@ impl Ord for LazyDeduplicate {
         self.arr.cmp(&other.arr)
     }
 }
+
+/// An array whose items are the columns of an array of rows
+///
+/// Its length is the length of the first row.
+#[derive(Debug, PartialEq, Eq)]
+pub struct LazyTranspose {
+    arr: Array,
+    span: Span,
+}
+
+impl LazyTranspose {
+    pub fn new(arr: Array, span: Span) -> Self {
+        LazyTranspose { arr, span }
+    }
+    pub fn len(&self) -> Option<usize> {
+        match self.arr.get(0).ok()??.as_ref() {
+            Val::Array(row) => row.len(),
+            Val::Atom(_) => Some(1),
+        }
+    }
+    pub fn get(&self, index: usize) -> Option<Val> {
+        if self.len().is_some_and(|len| index >= len) {
+            return None;
+        }
+        Some(
+            Array::Column(Rc::new(LazyColumn {
+                arr: self.arr.clone(),
+                index,
+                span: self.span.clone(),
+            }))
+            .into(),
+        )
+    }
+}
+
+/// A column of an array of rows
+#[derive(Debug, PartialEq, Eq)]
+pub struct LazyColumn {
+    arr: Array,
+    index: usize,
+    span: Span,
+}
+
+impl LazyColumn {
+    pub fn get(&self, index: usize) -> RuntimeResult<Option<Val>> {
+        let row = if let Some(row) = self.arr.get(index)? {
+            row
+        } else {
+            return Ok(None);
+        };
+        let item = match row.as_ref() {
+            Val::Array(row) => row.get(self.index)?.map(Cow::into_owned),
+            Val::Atom(_) if self.index == 0 => Some(row.into_owned()),
+            Val::Atom(_) => None,
+        };
+        match item {
+            Some(item) => Ok(Some(item)),
+            None => rt_error("Cannot transpose rows of different lengths", &self.span),
+        }
+    }
+}
+
+#[derive(Debug, PartialEq, Eq)]
+pub struct LazyRandom {
+    seed: u64,
+    below: Num,
+}
+
+impl LazyRandom {
+    pub fn new(seed: u64, below: Num) -> Self {
+        LazyRandom { seed, below }
+    }
+    pub fn get(&self, index: usize) -> Num {
+        random_num(random::nth(self.seed, index as u64), self.below)
+    }
+}
+
+/// Turn random bits into a random integer below some number,
+/// or a random float in [0, 1) if the number is 0
+pub fn random_num(bits: u64, below: Num) -> Num {
+    let f = random::unit_float(bits);
+    if below == 0 {
+        f.into()
+    } else {
+        (Num::from(f) * below).floor()
+    }
+}
+
+#[test]
+fn negative_drop() {
+    use crate::eval::eval_test;
+    assert_eq!(eval_test("‾2ᚷ⟨1, 2, 3, 4, 5⟩").unwrap(), "⟨1 2 3⟩");
+    assert_eq!(eval_test("‾2ᚷ\"abcde\"").unwrap(), "\"abc\"");
+}
+
+#[test]
+fn char_arrays() {
+    use crate::eval::eval_test;
+    let s = "s ← \"ᚠᚢᚦ runes ᚨ\"\n";
+    assert_eq!(eval_test(&format!("{s}3ᚾs")).unwrap(), "\"ᚠᚢᚦ\"");
+    assert_eq!(eval_test(&format!("{s}4ᚷs")).unwrap(), "\"runes ᚨ\"");
+    assert_eq!(eval_test(&format!("{s}ᛃs")).unwrap(), "\"ᚨ senur ᚦᚢᚠ\"");
+    assert_eq!(eval_test(&format!("{s}4ᛈs")).unwrap(), "'r'");
+    assert_eq!(eval_test(&format!("{s}‾1ᛈs")).unwrap(), "'ᚨ'");
+    assert_eq!(eval_test("\"ᚠᚢ\" ᛇ \"ᚦ\"").unwrap(), "\"ᚠᚢᚦ\"");
+    assert_eq!(eval_test("\"ab\" ᛇ 'c'").unwrap(), "\"abc\"");
+    assert_eq!(eval_test("\"ab\" ᛇ ⟨1⟩").unwrap(), "⟨'a' 'b' 1⟩");
+    assert_eq!(eval_test("\"ᚠᚢ\" ≡ ⟨'ᚠ', 'ᚢ'⟩").unwrap(), "1");
+}
@@ -74,7 +74,7 @@ pub enum Expr {
     BinMod(Sp<RuneBinMod>),
     Param(Sp<Param>),
     Ident(Sp<Ident>),
-    Num(Sp<Num>),
+    Num(Sp<NumLiteral>),
     Char(Sp<char>),
     String(Sp<Rc<str>>),
     Array(ArrayExpr),
@@ -83,6 +83,8 @@ pub enum Expr {
     Bin(Box<BinExpr>),
     Assign(Box<AssignExpr>),
     Function(Box<FunctionLiteral>),
+    Namespace(Box<NamespaceLiteral>),
+    Field(Box<FieldExpr>),
 }
 
 impl Expr {
@@ -112,7 +114,7 @@ impl Expr {
             Parened(expr) => expr.role(),
             Un(expr) => expr.op.role().un(expr.inner.role()),
             Bin(expr) => expr.op.role().bin(expr.left.role(), expr.right.role()),
-            Assign(expr) => expr.name.role(),
+            Assign(expr) => expr.pattern.role(),
             Function(items) => items.expressions().fold(Role::Function, |max, expr| {
                 let expr_role = expr
                     .max_param()
@@ -120,11 +122,14 @@ impl Expr {
                     .unwrap_or(Role::Function);
                 max.max(expr_role)
             }),
+            Namespace(_) => Role::Value,
+            Field(expr) => expr.name.role(),
         }
     }
     pub fn max_param(&self) -> Option<&Sp<Param>> {
         use Expr::*;
         match self {
+            Param(param) if param.place.is_self() => None,
             Param(param) => Some(param),
             Array(expr) => expr
                 .items
@@ -137,7 +142,16 @@ impl Expr {
                 .max_param()
                 .max(expr.left.max_param())
                 .max(expr.right.max_param()),
-            Assign(expr) => expr.body.max_param(),
+            Assign(expr) => expr
+                .function
+                .as_ref()
+                .and_then(Expr::max_param)
+                .max(expr.body.as_ref().and_then(Expr::max_param)),
+            Namespace(ns) => ns.items.iter().fold(None, |acc, item| match item {
+                Item::Expr(item) => item.expr.max_param().max(acc),
+                _ => acc,
+            }),
+            Field(expr) => expr.ns.max_param(),
             _ => None,
         }
     }
@@ -157,6 +171,8 @@ impl Expr {
             Expr::Bin(expr) => expr.op.span(),
             Expr::Assign(expr) => &expr.span,
             Expr::Function(body) => &body.span,
+            Expr::Namespace(ns) => &ns.span,
+            Expr::Field(expr) => &expr.name.span,
         }
     }
 }
@@ -182,6 +198,8 @@ impl fmt::Debug for Expr {
             Expr::Bin(expr) => expr.fmt(f),
             Expr::Assign(expr) => expr.fmt(f),
             Expr::Function(expr) => expr.fmt(f),
+            Expr::Namespace(expr) => expr.fmt(f),
+            Expr::Field(expr) => write!(f, "{:?}.{}", expr.ns, expr.name.data),
         }
     }
 }
@@ -207,11 +225,36 @@ impl Format for Expr {
             Expr::Bin(expr) => expr.format(f)?,
             Expr::Assign(expr) => expr.format(f)?,
             Expr::Function(func) => func.format(f)?,
+            Expr::Namespace(ns) => ns.format(f)?,
+            Expr::Field(expr) => {
+                expr.ns.format(f)?;
+                f.display('.');
+                f.display(&expr.name.data);
+            }
         }
         Ok(())
     }
 }
 
+#[derive(Clone)]
+pub struct NumLiteral {
+    pub num: Num,
+    /// The number as it was written
+    pub string: Rc<str>,
+}
+
+impl fmt::Debug for NumLiteral {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        self.num.fmt(f)
+    }
+}
+
+impl fmt::Display for NumLiteral {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        self.num.string_format(&self.string).fmt(f)
+    }
+}
+
 pub struct UnExpr {
     pub op: Expr,
     pub inner: Expr,
@@ -324,25 +367,75 @@ impl Format for ForkExpr {
 }
 
 pub struct AssignExpr {
-    pub name: Ident,
+    pub pattern: Pattern,
     pub op: AssignOp,
-    pub body: Expr,
+    /// The function of a modified assignment
+    pub function: Option<Expr>,
+    /// The assigned value, or the right argument of a modified assignment
+    pub body: Option<Expr>,
     pub span: Span,
 }
 
 impl fmt::Debug for AssignExpr {
     fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
-        write!(f, "({} {} {:?})", self.name, self.op, self.body)
+        write!(f, "({} ", self.pattern)?;
+        if let Some(function) = &self.function {
+            write!(f, "{:?}", function)?;
+        }
+        write!(f, "{}", self.op)?;
+        if let Some(body) = &self.body {
+            write!(f, " {:?}", body)?;
+        }
+        write!(f, ")")
     }
 }
 
 impl Format for AssignExpr {
     fn format(&self, f: &mut Formatter) -> RuntimeResult<()> {
-        f.display(&self.name);
+        f.display(&self.pattern);
         f.display(' ');
+        if let Some(function) = &self.function {
+            function.format(f)?;
+        }
         f.display(self.op);
-        f.display(' ');
-        self.body.format(f)
+        if let Some(body) = &self.body {
+            f.display(' ');
+            body.format(f)?;
+        }
+        Ok(())
+    }
+}
+
+/// The names an assignment binds to
+pub enum Pattern {
+    Ident(Ident),
+    Array(Vec<Pattern>),
+}
+
+impl Pattern {
+    pub fn role(&self) -> Role {
+        match self {
+            Pattern::Ident(ident) => ident.role(),
+            Pattern::Array(_) => Role::Value,
+        }
+    }
+}
+
+impl fmt::Display for Pattern {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Pattern::Ident(ident) => ident.fmt(f),
+            Pattern::Array(patterns) => {
+                write!(f, "⟨")?;
+                for (i, pattern) in patterns.iter().enumerate() {
+                    if i > 0 {
+                        write!(f, ", ")?;
+                    }
+                    pattern.fmt(f)?;
+                }
+                write!(f, "⟩")
+            }
+        }
     }
 }
 
@@ -420,14 +513,68 @@ impl Format for FunctionLiteral {
             f.indent(2);
         }
         for item in &self.items {
+            if self.items.len() > 1 {
+                f.newline();
+            }
             item.format(f)?;
         }
         if self.items.len() == 1 {
             f.display(' ');
         } else {
             f.deindent(2);
+            f.newline();
         }
         f.display('⦒');
         Ok(())
     }
 }
+
+pub struct NamespaceLiteral {
+    pub items: Vec<Item>,
+    pub span: Span,
+}
+
+impl fmt::Debug for NamespaceLiteral {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "⦃")?;
+        for item in &self.items {
+            item.fmt(f)?;
+            write!(f, ", ")?;
+        }
+        write!(f, "⦄")
+    }
+}
+
+impl Format for NamespaceLiteral {
+    fn format(&self, f: &mut Formatter) -> RuntimeResult<()> {
+        f.display('⦃');
+        if self.items.is_empty() {
+            f.display('⦄');
+            return Ok(());
+        }
+        if self.items.len() == 1 {
+            f.display(' ');
+        } else {
+            f.indent(2);
+        }
+        for item in &self.items {
+            if self.items.len() > 1 {
+                f.newline();
+            }
+            item.format(f)?;
+        }
+        if self.items.len() == 1 {
+            f.display(' ');
+        } else {
+            f.deindent(2);
+            f.newline();
+        }
+        f.display('⦄');
+        Ok(())
+    }
+}
+
+pub struct FieldExpr {
+    pub ns: Expr,
+    pub name: Sp<Ident>,
+}
@@ -0,0 +1,183 @@
+//! Parsing and writing delimiter-separated values
+//!
+//! Fields may be quoted with `"`, and a quote in a quoted field is written `""`.
+//! Unquoted fields that look like numbers are parsed as numbers.
+
+use crate::{
+    array::Array,
+    error::RuntimeResult,
+    eval::rt_error,
+    lex::Span,
+    num::Num,
+    value::{Atom, Val},
+};
+
+/// Get the delimiter from the left argument of a CSV operator
+pub fn delimiter(w: Option<Val>, span: &Span) -> RuntimeResult<char> {
+    match w {
+        None => Ok(','),
+        Some(Val::Atom(Atom::Char(c))) if c != '"' && c != '\n' && c != '\r' => Ok(c),
+        Some(w) => rt_error(
+            format!("{} cannot be used as a delimiter", w.type_name()),
+            span,
+        ),
+    }
+}
+
+/// Parse text into an array of rows
+pub fn parse(text: &str, delim: char, span: &Span) -> RuntimeResult<Array> {
+    let mut rows = Vec::new();
+    let mut row = Vec::new();
+    let mut chars = text.chars().peekable();
+    while chars.peek().is_some() {
+        // Parse a field
+        let mut field = String::new();
+        let quoted = chars.peek() == Some(&'"');
+        if quoted {
+            chars.next();
+            loop {
+                match chars.next() {
+                    Some('"') if chars.peek() == Some(&'"') => {
+                        chars.next();
+                        field.push('"');
+                    }
+                    Some('"') => break,
+                    Some(c) => field.push(c),
+                    None => return rt_error("Unclosed quoted field", span),
+                }
+            }
+        }
+        while let Some(&c) = chars.peek() {
+            if c == delim || c == '\n' || c == '\r' {
+                break;
+            }
+            field.push(c);
+            chars.next();
+        }
+        let blank = !quoted && field.is_empty();
+        row.push(if quoted {
+            Array::string(field).into()
+        } else {
+            field_val(field)
+        });
+        // Parse the end of the field
+        match chars.next() {
+            // A trailing delimiter ends with an empty field
+            Some(c) if c == delim && chars.peek().is_none() => row.push(Array::string("").into()),
+            Some(c) if c == delim => {}
+            Some(c) => {
+                if c == '\r' && chars.peek() == Some(&'\n') {
+                    chars.next();
+                }
+                // Skip blank lines
+                if blank && row.len() == 1 {
+                    row.clear();
+                } else {
+                    rows.push(Array::concrete(row.drain(..)));
+                }
+            }
+            None => {}
+        }
+    }
+    if !row.is_empty() {
+        rows.push(Array::concrete(row));
+    }
+    Ok(Array::concrete(rows))
+}
+
+fn field_val(field: String) -> Val {
+    let looks_numeric = field
+        .trim()
+        .starts_with(|c: char| c.is_ascii_digit() || "+-.".contains(c));
+    match field.trim().parse::<Num>() {
+        Ok(num) if looks_numeric => num.into(),
+        _ => Array::string(field).into(),
+    }
+}
+
+/// Write an array of rows as text
+pub fn write(x: &Val, delim: char, span: &Span) -> RuntimeResult<String> {
+    let mut text = String::new();
+    for row in finite_items(x, "rows", span)? {
+        let fields = if row.as_text()?.is_some() {
+            vec![row]
+        } else {
+            finite_items(&row, "fields", span)?
+        };
+        for (i, field) in fields.iter().enumerate() {
+            if i > 0 {
+                text.push(delim);
+            }
+            let field = match field {
+                Val::Atom(Atom::Num(Num::Int(i))) => i.to_string(),
+                Val::Atom(Atom::Num(Num::Float(f))) => f.to_string(),
+                Val::Atom(Atom::Char(c)) => c.to_string(),
+                field => match field.as_text()? {
+                    Some(s) => s,
+                    None => {
+                        return rt_error(
+                            format!("{} cannot be written as a field", field.type_name()),
+                            span,
+                        )
+                    }
+                },
+            };
+            if field.contains([delim, '"', '\n', '\r']) {
+                text.push('"');
+                text.push_str(&field.replace('"', "\"\""));
+                text.push('"');
+            } else {
+                text.push_str(&field);
+            }
+        }
+        text.push('\n');
+    }
+    Ok(text)
+}
+
+fn finite_items(val: &Val, name: &str, span: &Span) -> RuntimeResult<Vec<Val>> {
+    match val {
+        Val::Array(arr) if arr.len().is_some() => arr.clone().into_vec(),
+        Val::Array(_) => rt_error(format!("CSV {} must be finite", name), span),
+        val => rt_error(
+            format!(
+                "CSV {} must be an array, but it is {}",
+                name,
+                val.type_name()
+            ),
+            span,
+        ),
+    }
+}
+
+#[test]
+fn csv() {
+    use crate::eval::eval_test;
+    assert_eq!(
+        eval_test(r#"•Csv "a,\"b,c\",3\n\n\"x\"\"y\",,4.5""#).unwrap(),
+        "\"a\"\n\"b,c\"\n3\n\n\"x\\\"y\"\n⟨⟩\n4.5\n\n"
+    );
+    assert_eq!(eval_test(r#"';' •Csv "1;2""#).unwrap(), "⟨1 2⟩\n");
+    assert_eq!(
+        eval_test(r#"•ToCsv ⟨⟨"a", 1⟩, ⟨"b,c", "q\"r"⟩⟩"#).unwrap(),
+        r#""a,1\n\"b,c\",\"q\"\"r\"\n""#
+    );
+    assert_eq!(
+        eval_test(r#"';' •ToCsv ⟨⟨1, 2⟩, ⟨"x;y", 3⟩⟩"#).unwrap(),
+        r#""1;2\n\"x;y\";3\n""#
+    );
+    assert_eq!(
+        eval_test("rows ← ⟨⟨\"a\", 1⟩, ⟨\"b,c\", \"q\\\"r\"⟩⟩\nrows ≡ •Csv •ToCsv rows").unwrap(),
+        "1"
+    );
+    let rows = "rows ← ⟨⟨1, 2, 3⟩, ⟨4, 5, 6⟩⟩\n";
+    assert_eq!(
+        eval_test(&format!("{rows}ᚢ rows")).unwrap(),
+        "⟨1 4⟩\n⟨2 5⟩\n⟨3 6⟩\n"
+    );
+    assert_eq!(eval_test(&format!("{rows}rows ≡ ᛠᚢ ᚢ rows")).unwrap(), "1");
+    assert_eq!(
+        eval_test("ᚢ ⟨⟨1, 2⟩, ⟨3⟩⟩").unwrap_err(),
+        "Cannot transpose rows of different lengths"
+    );
+}
@@ -1,27 +1,30 @@
 //! Types for and conversion into the Concrete Walkable Tree
 
-use std::{collections::HashSet, rc::Rc};
+use std::{collections::HashMap, rc::Rc};
 
 use crate::{
     array::Array,
     ast::*,
     error::{CompileError, Problem, SpannedCompileWarning},
     function::*,
-    lex::{Ident, Param, ParamPlace, Span},
+    lex::{Ident, Param, ParamPlace, Role, Span},
     op::AssignOp,
     rcview::RcView,
-    value::{Atom, Val},
+    value::Val,
 };
 
 #[derive(Debug, Clone, PartialEq, Eq, PartialOrd, Ord)]
 pub enum ValNode {
     Param(Param),
-    Ident(Ident),
+    Ident(Rc<IdentValNode>),
     Val(Val),
     Un(Rc<UnValNode>),
     Bin(Rc<BinValNode>),
     Array(Rc<[Self]>),
     Assign(Rc<AssignValNode>),
+    Literal(Rc<LiteralValNode>),
+    Namespace(Rc<NamespaceValNode>),
+    Field(Rc<FieldValNode>),
 }
 
 #[derive(Debug, PartialEq, Eq, PartialOrd, Ord)]
@@ -41,9 +44,59 @@ pub struct BinValNode {
 
 #[derive(Debug, PartialEq, Eq, PartialOrd, Ord)]
 pub struct AssignValNode {
-    pub name: Ident,
+    pub pattern: PatternNode,
     pub op: AssignOp,
     pub body: ValNode,
+    pub span: Span,
+}
+
+#[derive(Debug, PartialEq, Eq, PartialOrd, Ord)]
+pub struct IdentValNode {
+    pub name: Ident,
+    pub slot: Slot,
+    pub span: Span,
+}
+
+/// The names an assignment binds to, with their resolved slots
+#[derive(Debug, Clone, PartialEq, Eq, PartialOrd, Ord)]
+pub enum PatternNode {
+    Ident(Ident, Slot),
+    Array(Rc<[PatternNode]>),
+}
+
+/// The location of a binding, resolved when the tree is built
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, PartialOrd, Ord)]
+pub struct Slot {
+    /// The number of scopes up from the current one
+    pub depth: usize,
+    /// The index of the binding in its scope
+    pub index: usize,
+}
+
+/// A function or modifier literal
+///
+/// Evaluating it captures the current runtime in a [`Closure`].
+#[derive(Debug, PartialEq, Eq, PartialOrd, Ord)]
+pub struct LiteralValNode {
+    pub role: Role,
+    pub nodes: RcView<ValNode>,
+}
+
+/// A namespace literal
+///
+/// Its fields are the bindings of its scope, in slot order.
+#[derive(Debug, PartialEq, Eq, PartialOrd, Ord)]
+pub struct NamespaceValNode {
+    pub nodes: RcView<ValNode>,
+    pub fields: Rc<[(Ident, usize)]>,
+    pub span: Span,
+}
+
+#[derive(Debug, PartialEq, Eq, PartialOrd, Ord)]
+pub struct FieldValNode {
+    pub ns: ValNode,
+    pub name: Ident,
+    pub span: Span,
 }
 
 impl From<UnValNode> for ValNode {
@@ -91,11 +144,13 @@ impl FromIterator<ValNode> for ValNode {
 pub struct TreeBuilder {
     problems: Vec<Problem>,
     scopes: Vec<Scope>,
+    /// The roles of the function literals being built
+    literals: Vec<Role>,
 }
 
 #[derive(Default)]
 struct Scope {
-    bindings: HashSet<Ident>,
+    bindings: HashMap<Ident, usize>,
 }
 
 pub type TreeBuildResult = Result<(ValNode, Vec<SpannedCompileWarning>), Vec<Problem>>;
@@ -105,6 +160,7 @@ impl Default for TreeBuilder {
         TreeBuilder {
             problems: Vec::new(),
             scopes: vec![Scope::default()],
+            literals: Vec::new(),
         }
     }
 }
@@ -143,11 +199,33 @@ impl TreeBuilder {
     fn scope(&mut self) -> &mut Scope {
         self.scopes.last_mut().expect("scopes is empty")
     }
-    pub fn lookup(&self, name: &Ident) -> bool {
+    pub fn lookup(&self, name: &Ident) -> Option<Slot> {
         self.scopes
             .iter()
             .rev()
-            .any(|scope| scope.bindings.contains(name))
+            .enumerate()
+            .find_map(|(depth, scope)| {
+                let index = *scope.bindings.get(name)?;
+                Some(Slot { depth, index })
+            })
+    }
+    /// The bindings of the current scope, in slot order
+    pub fn fields(&self) -> Rc<[(Ident, usize)]> {
+        let scope = self.scopes.last().expect("scopes is empty");
+        let mut fields: Vec<(Ident, usize)> = scope
+            .bindings
+            .iter()
+            .map(|(name, index)| (name.clone(), *index))
+            .collect();
+        fields.sort_by_key(|(_, index)| *index);
+        fields.into()
+    }
+    /// Get the slot for a binding in the current scope, adding it if it is new
+    fn declare(&mut self, name: Ident) -> Slot {
+        let bindings = &mut self.scope().bindings;
+        let next = bindings.len();
+        let index = *bindings.entry(name).or_insert(next);
+        Slot { depth: 0, index }
     }
 }
 
@@ -170,19 +248,41 @@ impl ToValNode for ExprItem {
 impl ToValNode for Expr {
     fn to_val(&self, builder: &mut TreeBuilder) -> ValNode {
         match self {
-            Expr::Param(param) => ValNode::Param(param.data),
+            Expr::Param(param) => {
+                // Like other params, self params refer to the innermost literal
+                let literal = builder.literals.last();
+                let allowed = match param.place {
+                    ParamPlace::SelfFunction => literal.is_some(),
+                    ParamPlace::SelfUnMod => literal == Some(&Role::UnModifier),
+                    ParamPlace::SelfBinMod => literal == Some(&Role::BinModifier),
+                    _ => true,
+                };
+                if !allowed {
+                    builder
+                        .error(CompileError::SelfOutsideLiteral(param.data).at(param.span.clone()));
+                }
+                ValNode::Param(param.data)
+            }
             Expr::Op(op) => (**op).into(),
             Expr::UnMod(m) => UnMod::from(**m).into(),
             Expr::BinMod(m) => BinMod::from(**m).into(),
             Expr::Ident(ident) => {
-                if !builder.lookup(ident) {
+                let slot = builder.lookup(ident).unwrap_or_else(|| {
                     builder.error(
                         CompileError::UnknownBinding(ident.data.clone()).at(ident.span.clone()),
-                    )
-                }
-                ValNode::Ident(ident.data.clone())
+                    );
+                    Slot::default()
+                });
+                ValNode::Ident(
+                    IdentValNode {
+                        name: ident.data.clone(),
+                        slot,
+                        span: ident.span.clone(),
+                    }
+                    .into(),
+                )
             }
-            Expr::Num(n) => (**n).into(),
+            Expr::Num(n) => n.num.into(),
             Expr::Char(c) => (**c).into(),
             Expr::String(s) => Array::string(s.data.clone()).into(),
             Expr::Array(arr) => ValNode::Array(
@@ -196,6 +296,15 @@ impl ToValNode for Expr {
             Expr::Bin(expr) => expr.to_val(builder),
             Expr::Assign(expr) => expr.to_val(builder),
             Expr::Function(func) => func.to_val(builder),
+            Expr::Namespace(ns) => ns.to_val(builder),
+            Expr::Field(expr) => ValNode::Field(
+                FieldValNode {
+                    ns: expr.ns.to_val(builder),
+                    name: expr.name.data.clone(),
+                    span: expr.name.span.clone(),
+                }
+                .into(),
+            ),
         }
     }
 }
@@ -229,34 +338,198 @@ impl ToValNode for BinExpr {
 
 impl ToValNode for AssignExpr {
     fn to_val(&self, builder: &mut TreeBuilder) -> ValNode {
-        if self.name.role() != self.body.role() {
-            builder.error(
-                CompileError::MismatchedRoles(self.name.clone(), self.body.role())
-                    .at(self.span.clone()),
-            );
+        if let (None, Some(body)) = (&self.function, &self.body) {
+            builder.check_roles(&self.pattern, body, &self.span);
         }
-        builder.scope().bindings.insert(self.name.clone());
+        // The body is built before the pattern declares its names,
+        // so that it sees any bindings they shadow
+        let function = self
+            .function
+            .as_ref()
+            .map(|function| (function.to_val(builder), function.span().clone()));
+        let body = self.body.as_ref().map(|body| body.to_val(builder));
+        let pattern = builder.pattern(&self.pattern, self.op, &self.span);
+        let body = match (function, body, &pattern) {
+            (None, Some(body), _) => body,
+            // `name F↩ x` is `name ↩ name F x`
+            (Some((op, span)), body, PatternNode::Ident(name, slot)) => {
+                let name = ValNode::Ident(
+                    IdentValNode {
+                        name: name.clone(),
+                        slot: *slot,
+                        span: self.span.clone(),
+                    }
+                    .into(),
+                );
+                if let Some(body) = body {
+                    BinValNode {
+                        op,
+                        left: name,
+                        right: body,
+                        span,
+                    }
+                    .into()
+                } else {
+                    UnValNode {
+                        op,
+                        inner: name,
+                        span,
+                    }
+                    .into()
+                }
+            }
+            _ => unreachable!("invalid assignment form"),
+        };
         ValNode::Assign(
             AssignValNode {
-                name: self.name.clone(),
+                pattern,
                 op: self.op,
-                body: self.body.to_val(builder),
+                body,
+                span: self.span.clone(),
             }
             .into(),
         )
     }
 }
 
+impl TreeBuilder {
+    /// Resolve the slots of the names in a pattern
+    fn pattern(&mut self, pattern: &Pattern, op: AssignOp, span: &Span) -> PatternNode {
+        match pattern {
+            Pattern::Ident(name) => {
+                let slot = match op {
+                    AssignOp::Assign => self.declare(name.clone()),
+                    AssignOp::Reassign => self.lookup(name).unwrap_or_else(|| {
+                        self.error(CompileError::ReassignUnbound(name.clone()).at(span.clone()));
+                        Slot::default()
+                    }),
+                };
+                PatternNode::Ident(name.clone(), slot)
+            }
+            Pattern::Array(patterns) => PatternNode::Array(
+                patterns
+                    .iter()
+                    .map(|pattern| self.pattern(pattern, op, span))
+                    .collect(),
+            ),
+        }
+    }
+    /// Check that each name in a pattern matches the role of the value it is bound to
+    ///
+    /// The roles of array items are only known if the value is an array literal.
+    fn check_roles(&mut self, pattern: &Pattern, body: &Expr, span: &Span) {
+        match (pattern, body) {
+            (Pattern::Ident(name), body) => {
+                if name.role() != body.role() {
+                    self.error(
+                        CompileError::MismatchedRoles(name.clone(), body.role()).at(span.clone()),
+                    );
+                }
+            }
+            (pattern, Expr::Parened(body)) => self.check_roles(pattern, body, span),
+            (Pattern::Array(patterns), Expr::Array(arr)) if patterns.len() == arr.items.len() => {
+                for (pattern, (item, _)) in patterns.iter().zip(&arr.items) {
+                    self.check_roles(pattern, item, span);
+                }
+            }
+            (Pattern::Array(_), body) => {
+                if body.role() != Role::Value {
+                    self.error(
+                        CompileError::InvalidRole(body.role(), vec![Role::Value]).at(span.clone()),
+                    );
+                }
+            }
+        }
+    }
+}
+
 impl ToValNode for FunctionLiteral {
     fn to_val(&self, builder: &mut TreeBuilder) -> ValNode {
+        let role = match self.max_param().map(|param| param.place) {
+            Some(ParamPlace::F) => Role::UnModifier,
+            Some(ParamPlace::G) => Role::BinModifier,
+            _ => Role::Function,
+        };
+        builder.scopes.push(Scope::default());
+        builder.literals.push(role);
         let nodes: RcView<ValNode> = self
             .expressions()
             .map(|expr| expr.to_val(builder))
             .collect();
-        match self.max_param().map(|param| param.place) {
-            Some(ParamPlace::W | ParamPlace::X) | None => Function::Nodes(nodes).into(),
-            Some(ParamPlace::F) => Atom::UnMod(UnMod::Nodes(nodes)).into(),
-            Some(ParamPlace::G) => Atom::BinMod(BinMod::Nodes(nodes)).into(),
-        }
+        builder.literals.pop();
+        builder.scopes.pop();
+        ValNode::Literal(LiteralValNode { role, nodes }.into())
     }
 }
+
+impl ToValNode for NamespaceLiteral {
+    fn to_val(&self, builder: &mut TreeBuilder) -> ValNode {
+        builder.scopes.push(Scope::default());
+        let nodes: RcView<ValNode> = self
+            .items
+            .iter()
+            .filter_map(|item| match item {
+                Item::Expr(item) => Some(item.expr.to_val(builder)),
+                _ => None,
+            })
+            .collect();
+        let fields = builder.fields();
+        builder.scopes.pop();
+        ValNode::Namespace(
+            NamespaceValNode {
+                nodes,
+                fields,
+                span: self.span.clone(),
+            }
+            .into(),
+        )
+    }
+}
+
+#[test]
+fn self_params_need_their_literal() {
+    use crate::eval::eval_test;
+    let error = |code| eval_test(code).unwrap_err();
+    assert_eq!(error("ᚩ 1"), "ᚩ can only occur within functions");
+    assert_eq!(error("⦑ᚪ⦒"), "ᚪ can only occur within unary modifiers");
+    assert_eq!(
+        error("_M ← ⦑ᚯ ᚫ⦒"),
+        "ᚫ can only occur within binary modifiers"
+    );
+    assert_eq!(
+        error("_M ← ⦑ᚯ ⦑ᚪ⦒ 0⦒"),
+        "ᚪ can only occur within unary modifiers"
+    );
+    assert!(eval_test("F ← ⦑ᚩ⦒").is_ok());
+    let un = "_M ← ⦑ᛞ(ᛚ>ᛜ100)(ᛜ⟨ᚻ(ᚪᚯ)ᚯ, ᛚ⟩) ᚭ⦒\n_M(2×ᛚ) 1";
+    assert_eq!(eval_test(un).unwrap(), "128");
+    let bin = "_M_ ← ⦑ᛞ(ᛚ>ᛜ100)(ᛜ⟨ᚻ(ᚫᚯᚬ)ᚬ, ᚯ⟩) ᚭ⦒\n_M_(ᛚ᛭ᛜ1)(2×ᛚ) 1";
+    assert_eq!(eval_test(bin).unwrap(), "129");
+}
+
+#[test]
+fn slot_resolution() {
+    use crate::eval::eval_test;
+    // Inner bindings shadow outer ones only inside the literal
+    assert_eq!(
+        eval_test("x ← 1\nF ← ⦑x ← 2\nx ᛭ ᚭ⦒\n⟨F 10, x⟩").unwrap(),
+        "⟨12 1⟩"
+    );
+    // Names resolve through several levels of literals
+    assert_eq!(
+        eval_test("a ← 1\nF ← ⦑b ← 2\n⦑a ᛭ b ᛭ ᚭ⦒ 3⦒\nF 0").unwrap(),
+        "6"
+    );
+    // A binding's own value sees the binding it shadows
+    assert_eq!(eval_test("x ← 5\nF ← ⦑x ← x ᛭ ᚭ⦒\nF 1").unwrap(), "6");
+    assert_eq!(eval_test("F ← ⦑y⦒").unwrap_err(), "Unknown binding `y`");
+}
+
+#[test]
+fn binding_errors() {
+    use crate::eval::eval_test;
+    assert_eq!(eval_test("z").unwrap_err(), "Unknown binding `z`");
+    assert!(eval_test("y ↩ 1")
+        .unwrap_err()
+        .starts_with("Cannot reassign `y` because it is not bound"));
+}
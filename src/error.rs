@@ -3,8 +3,8 @@ use std::{error::Error, fmt, io};
 use colored::{Color, Colorize};
 
 use crate::{
-    lex::{Ident, Role, Span},
-    op::Op,
+    lex::{Ident, Param, ParamPlace, Role, Span},
+    op::{AssignOp, Op, SYSTEM_PREFIX},
     value::Val,
 };
 
@@ -21,9 +21,12 @@ pub enum CompileError {
     NoBinaryImplementation(Op),
     NoUnaryImplementation(Op),
     UnknownBinding(Ident),
+    ReassignUnbound(Ident),
+    UnknownSystemName(String),
     MismatchedRoles(Ident, Role),
     InvalidRole(Role, Vec<Role>),
     ParameterOutsideFunction,
+    SelfOutsideLiteral(Param),
     EmptyFunction,
 }
 
@@ -50,6 +53,15 @@ impl fmt::Display for CompileError {
                 write!(f, "{} has no unary implementation", op)
             }
             CompileError::UnknownBinding(name) => write!(f, "Unknown binding `{}`", name),
+            CompileError::ReassignUnbound(name) => write!(
+                f,
+                "Cannot reassign `{}` because it is not bound. Use `{}` to bind it.",
+                name,
+                AssignOp::Assign
+            ),
+            CompileError::UnknownSystemName(name) => {
+                write!(f, "Unknown system name `{}{}`", SYSTEM_PREFIX, name)
+            }
             CompileError::MismatchedRoles(name, role) => {
                 writeln!(
                     f,
@@ -79,6 +91,14 @@ impl fmt::Display for CompileError {
             CompileError::ParameterOutsideFunction => {
                 write!(f, "Parameters can only occur within functions")
             }
+            CompileError::SelfOutsideLiteral(param) => {
+                let kind = match param.place {
+                    ParamPlace::SelfUnMod => "unary modifiers",
+                    ParamPlace::SelfBinMod => "binary modifiers",
+                    _ => "functions",
+                };
+                write!(f, "{} can only occur within {}", param, kind)
+            }
             CompileError::EmptyFunction => {
                 write!(f, "Functions must contain at least one expression")
             }
@@ -245,6 +265,15 @@ impl From<RuntimeError> for fmt::Error {
     }
 }
 
+impl From<Problem> for RuntimeError {
+    fn from(problem: Problem) -> Self {
+        match problem {
+            Problem::Error(e) => RuntimeError::new(e.kind.to_string(), e.span),
+            Problem::Warning(w) => RuntimeError::new(w.kind.to_string(), w.span),
+        }
+    }
+}
+
 impl RuntimeError {
     pub fn new(message: impl Into<String>, span: Span) -> Self {
         RuntimeError {
@@ -1,4 +1,7 @@
-use std::collections::{BTreeMap, BTreeSet};
+use std::{
+    collections::{BTreeMap, BTreeSet},
+    rc::Rc,
+};
 
 use crate::{
     array::*,
@@ -6,7 +9,8 @@ use crate::{
     error::{RuntimeError, RuntimeResult},
     format::Format,
     function::*,
-    lex::{ParamPlace, Span},
+    lex::{ParamPlace, Role, Span},
+    map::pairs_view,
     num::Num,
     op::*,
     pervade::{bin_pervade_val, un_pervade_val},
@@ -28,9 +32,7 @@ impl Eval for ValNode {
     fn eval(&self, rt: &Runtime) -> RuntimeResult {
         match self {
             ValNode::Param(param) => Ok(rt.get_param(param.place).unwrap_or_else(|| 0i64.into())),
-            ValNode::Ident(ident) => Ok(rt
-                .get(ident)
-                .unwrap_or_else(|| panic!("No value stored for `{}`", ident))),
+            ValNode::Ident(ident) => ident.eval(rt),
             ValNode::Val(val) => val.eval(rt),
             ValNode::Un(un) => un.eval(rt),
             ValNode::Bin(bin) => bin.eval(rt),
@@ -42,24 +44,45 @@ impl Eval for ValNode {
                 Ok(Val::from_iter(vals))
             }
             ValNode::Assign(assign) => assign.eval(rt),
+            ValNode::Literal(literal) => literal.eval(rt),
+            ValNode::Namespace(ns) => ns.eval(rt),
+            ValNode::Field(field) => field.eval(rt),
         }
     }
 }
 
+impl Eval for LiteralValNode {
+    fn eval(&self, rt: &Runtime) -> RuntimeResult {
+        let closure = Rc::new(Closure {
+            nodes: self.nodes.clone(),
+            rt: rt.clone(),
+        });
+        Ok(match self.role {
+            Role::UnModifier => Atom::UnMod(closure.into()).into(),
+            Role::BinModifier => Atom::BinMod(closure.into()).into(),
+            _ => Function::Nodes(closure).into(),
+        })
+    }
+}
+
+impl Eval for IdentValNode {
+    fn eval(&self, rt: &Runtime) -> RuntimeResult {
+        rt.get(self.slot).map_or_else(
+            || {
+                rt_error(
+                    format!("`{}` has not been bound yet", self.name),
+                    &self.span,
+                )
+            },
+            Ok,
+        )
+    }
+}
+
 impl Eval for AssignValNode {
     fn eval(&self, rt: &Runtime) -> RuntimeResult {
         let val = self.body.eval(rt)?;
-        match self.op {
-            AssignOp::Assign => rt.bind(self.name.clone(), val.clone()),
-            AssignOp::Reassign => {
-                if rt
-                    .get_mut(&self.name, |bound| *bound = val.clone())
-                    .is_none()
-                {
-                    panic!("attempted to set unbound variable")
-                }
-            }
-        }
+        rt.assign(&self.pattern, self.op, val.clone(), &self.span)?;
         Ok(val)
     }
 }
@@ -82,6 +105,52 @@ impl Eval for BinValNode {
 }
 
 impl Runtime {
+    fn assign(
+        &self,
+        pattern: &PatternNode,
+        op: AssignOp,
+        val: Val,
+        span: &Span,
+    ) -> RuntimeResult<()> {
+        match pattern {
+            PatternNode::Ident(name, slot) => match op {
+                AssignOp::Assign => self.bind(slot.index, val),
+                AssignOp::Reassign => {
+                    if self.get_mut(*slot, |bound| *bound = val).is_none() {
+                        return rt_error(
+                            format!(
+                                "Cannot reassign `{}` because it has not been bound yet",
+                                name
+                            ),
+                            span,
+                        );
+                    }
+                }
+            },
+            PatternNode::Array(patterns) => {
+                let items = match val {
+                    Val::Array(arr) if arr.len() == Some(patterns.len()) => arr.into_vec()?,
+                    Val::Array(arr) => {
+                        return rt_error(
+                            format!(
+                                "Cannot destructure an array of length {} into {} names",
+                                arr.len().map_or_else(|| "∞".into(), |len| len.to_string()),
+                                patterns.len()
+                            ),
+                            span,
+                        )
+                    }
+                    Val::Atom(atom) => {
+                        return rt_error(format!("Cannot destructure {}", atom.type_name()), span)
+                    }
+                };
+                for (pattern, item) in patterns.iter().zip(items) {
+                    self.assign(pattern, op, item, span)?;
+                }
+            }
+        }
+        Ok(())
+    }
     pub fn eval_nodes(&self, nodes: impl IntoIterator<Item = ValNode>) -> RuntimeResult {
         let mut res: Val = Num::Int(0).into();
         for node in nodes {
@@ -98,15 +167,15 @@ impl Runtime {
         }
     }
 
-    fn eval_un_function(&self, function: Function, x: Val, span: &Span) -> RuntimeResult {
+    pub fn eval_un_function(&self, function: Function, x: Val, span: &Span) -> RuntimeResult {
         if let Val::Atom(Atom::Function(g)) = x {
             return Ok(Function::Atop(Atop { f: function, g }.into()).into());
         }
         match function {
-            Function::Nodes(nodes) => {
-                let rt = self.push();
+            Function::Nodes(closure) => {
+                let rt = closure.rt.push_call(Function::Nodes(closure.clone()));
                 rt.bind_param(ParamPlace::X, x);
-                rt.eval_nodes(nodes)
+                rt.eval_nodes(closure.nodes.clone())
             }
             Function::Op(Op::Pervasive(Pervasive::Comparison(ComparisonOp::Equal))) => match x {
                 Val::Array(arr) => Ok(arr.len().map(Num::from).unwrap_or(Num::INFINIFY).into()),
@@ -116,6 +185,7 @@ impl Runtime {
             Function::Op(Op::Rune(rune)) => match rune {
                 RuneOp::Laguz => Ok(x),
                 RuneOp::Jera => self.reverse(x, span),
+                RuneOp::Uruz => self.transpose(x, span),
                 RuneOp::Algiz => self.range(x, span).map(Val::from),
                 RuneOp::Tiwaz => self.grade(x, span).map(Val::from),
                 RuneOp::Perth => self.first(x, span),
@@ -127,6 +197,7 @@ impl Runtime {
                 OtherOp::Match => x.depth(span).map(Into::into),
                 other => todo!("{:?}", other),
             },
+            Function::Op(Op::System(sys)) => self.system_un(sys, x, span),
             Function::Atop(atop) => {
                 let lower = self.eval_un_function(atop.g, x, span)?;
                 self.eval_un_function(atop.f, lower, span)
@@ -142,15 +213,22 @@ impl Runtime {
                     RuneUnMod::Raido => self.fold(un_mod.f, None, x, span),
                     RuneUnMod::Thurisaz => self.scan(un_mod.f, None, x, span).map(Val::from),
                     RuneUnMod::Othala => self.eval_bin(un_mod.f, x.clone(), x, span),
+                    RuneUnMod::Ear => self.undo(un_mod.f, None, x, span),
                     RuneUnMod::Berkanan | RuneUnMod::Wunjo => {
                         self.each_un(un_mod.f, x, span).map(Val::from)
                     }
                 },
-                UnMod::Nodes(nodes) => {
-                    let rt = self.push();
+                UnMod::Nodes(closure) => {
+                    let rt = closure.rt.push_call(
+                        UnModded {
+                            m: UnMod::Nodes(closure.clone()),
+                            f: un_mod.f.clone(),
+                        }
+                        .into(),
+                    );
                     rt.bind_param(ParamPlace::F, un_mod.f);
                     rt.bind_param(ParamPlace::X, x);
-                    rt.eval_nodes(nodes)
+                    rt.eval_nodes(closure.nodes.clone())
                 }
             },
             Function::BinMod(bin_mod) => match bin_mod.m {
@@ -168,14 +246,23 @@ impl Runtime {
                         let chosen = self.index(condition, branches, span)?;
                         self.eval_un(chosen, x, span)
                     }
+                    RuneBinMod::Mannaz => self.under(bin_mod.f, bin_mod.g, None, x, span),
+                    RuneBinMod::Ger => self.repeat(bin_mod.f, bin_mod.g, None, x, span),
                     m => todo!("{:?}", m),
                 },
-                BinMod::Nodes(nodes) => {
-                    let rt = self.push();
+                BinMod::Nodes(closure) => {
+                    let rt = closure.rt.push_call(
+                        BinModded {
+                            m: BinMod::Nodes(closure.clone()),
+                            f: bin_mod.f.clone(),
+                            g: bin_mod.g.clone(),
+                        }
+                        .into(),
+                    );
                     rt.bind_param(ParamPlace::F, bin_mod.f);
                     rt.bind_param(ParamPlace::G, bin_mod.g);
                     rt.bind_param(ParamPlace::X, x);
-                    rt.eval_nodes(nodes)
+                    rt.eval_nodes(closure.nodes.clone())
                 }
             },
         }
@@ -189,7 +276,13 @@ impl Runtime {
         }
     }
 
-    fn eval_bin_function(&self, function: Function, w: Val, x: Val, span: &Span) -> RuntimeResult {
+    pub fn eval_bin_function(
+        &self,
+        function: Function,
+        w: Val,
+        x: Val,
+        span: &Span,
+    ) -> RuntimeResult {
         if let Val::Atom(Atom::Function(right)) = x {
             return Ok(Function::Fork(
                 Fork {
@@ -202,19 +295,17 @@ impl Runtime {
             .into());
         }
         match function {
-            Function::Nodes(nodes) => {
-                let rt = self.push();
+            Function::Nodes(closure) => {
+                let rt = closure.rt.push_call(Function::Nodes(closure.clone()));
                 rt.bind_param(ParamPlace::X, x);
                 rt.bind_param(ParamPlace::W, w);
-                rt.eval_nodes(nodes)
+                rt.eval_nodes(closure.nodes.clone())
             }
             Function::Op(Op::Pervasive(per)) => bin_pervade_val(per, w, x, span),
             Function::Op(Op::Rune(rune)) => match rune {
                 RuneOp::Laguz => Ok(x),
                 RuneOp::Fehu => self.replicate(w, x, span).map(Val::from),
-                RuneOp::Iwaz => {
-                    Ok(Array::JoinTo(w.into_array().into(), x.into_array().into()).into())
-                }
+                RuneOp::Iwaz => Ok(w.into_array().join_to(x.into_array()).into()),
                 RuneOp::Naudiz => self.take(w, x, span).map(Val::from),
                 RuneOp::Gebo => self.drop(w, x, span).map(Val::from),
                 RuneOp::Perth => self.index(w, x, span),
@@ -227,6 +318,7 @@ impl Runtime {
                 OtherOp::Match => w.matches(&x).map(Val::from),
                 OtherOp::DoNotMatch => w.matches(&x).map(|matches| (!matches).into()),
             },
+            Function::Op(Op::System(sys)) => self.system_bin(sys, w, x, span),
             Function::Atop(atop) => {
                 let lower = self.eval_bin_function(atop.g, w, x, span)?;
                 self.eval_un_function(atop.f, lower, span)
@@ -242,15 +334,22 @@ impl Runtime {
                     RuneUnMod::Raido => self.fold(un_mod.f, Some(w), x, span),
                     RuneUnMod::Thurisaz => self.scan(un_mod.f, Some(w), x, span).map(Val::from),
                     RuneUnMod::Othala => self.eval_bin(un_mod.f, x, w, span),
+                    RuneUnMod::Ear => self.undo(un_mod.f, Some(w), x, span),
                     RuneUnMod::Berkanan => self.each_bin(un_mod.f, w, x, span).map(Val::from),
                     RuneUnMod::Wunjo => self.table(un_mod.f, w, x, span).map(Val::from),
                 },
-                UnMod::Nodes(nodes) => {
-                    let rt = self.push();
+                UnMod::Nodes(closure) => {
+                    let rt = closure.rt.push_call(
+                        UnModded {
+                            m: UnMod::Nodes(closure.clone()),
+                            f: un_mod.f.clone(),
+                        }
+                        .into(),
+                    );
                     rt.bind_param(ParamPlace::X, x);
                     rt.bind_param(ParamPlace::W, w);
                     rt.bind_param(ParamPlace::F, un_mod.f);
-                    rt.eval_nodes(nodes)
+                    rt.eval_nodes(closure.nodes.clone())
                 }
             },
             Function::BinMod(bin_mod) => match bin_mod.m {
@@ -270,27 +369,48 @@ impl Runtime {
                         let chosen = self.index(condition, branches, span)?;
                         self.eval_bin(chosen, w, x, span)
                     }
+                    RuneBinMod::Mannaz => self.under(bin_mod.f, bin_mod.g, Some(w), x, span),
+                    RuneBinMod::Ger => self.repeat(bin_mod.f, bin_mod.g, Some(w), x, span),
                     m => todo!("{:?}", m),
                 },
-                BinMod::Nodes(nodes) => {
-                    let rt = self.push();
+                BinMod::Nodes(closure) => {
+                    let rt = closure.rt.push_call(
+                        BinModded {
+                            m: BinMod::Nodes(closure.clone()),
+                            f: bin_mod.f.clone(),
+                            g: bin_mod.g.clone(),
+                        }
+                        .into(),
+                    );
                     rt.bind_param(ParamPlace::X, x);
                     rt.bind_param(ParamPlace::W, w);
                     rt.bind_param(ParamPlace::F, bin_mod.f);
                     rt.bind_param(ParamPlace::G, bin_mod.g);
-                    rt.eval_nodes(nodes)
+                    rt.eval_nodes(closure.nodes.clone())
                 }
             },
         }
     }
 
-    fn reverse(&self, x: Val, span: &Span) -> RuntimeResult<Val> {
+    pub fn reverse(&self, x: Val, span: &Span) -> RuntimeResult<Val> {
         match x {
             Val::Atom(_) => Ok(x),
             Val::Array(arr) if arr.len().is_none() => {
                 rt_error("Unbounded arrays cannot be reversed", span)
             }
-            Val::Array(arr) => Ok(Array::Reverse(arr.into()).into()),
+            Val::Array(arr) => Ok(arr.reverse().into()),
+        }
+    }
+
+    pub fn transpose(&self, x: Val, span: &Span) -> RuntimeResult<Val> {
+        match x {
+            Val::Array(arr) if arr.len().is_none() => {
+                rt_error("Unbounded arrays cannot be transposed", span)
+            }
+            Val::Array(arr) if matches!(arr.get(0)?.as_deref(), Some(Val::Array(_))) => {
+                Ok(Array::Transpose(LazyTranspose::new(arr, span.clone()).into()).into())
+            }
+            x => Ok(x),
         }
     }
 
@@ -345,7 +465,7 @@ impl Runtime {
 
     pub fn take(&self, w: Val, x: Val, span: &Span) -> RuntimeResult<Array> {
         match (w, x) {
-            (Val::Atom(Atom::Num(n)), Val::Array(arr)) => Ok(Array::Take(arr.into(), i64::from(n))),
+            (Val::Atom(Atom::Num(n)), Val::Array(arr)) => Ok(arr.take(i64::from(n))),
             (w, x) => rt_error(
                 format!(
                     "Attempted to take {} items from {}",
@@ -359,7 +479,7 @@ impl Runtime {
 
     pub fn drop(&self, w: Val, x: Val, span: &Span) -> RuntimeResult<Array> {
         match (w, x) {
-            (Val::Atom(Atom::Num(n)), Val::Array(arr)) => Ok(Array::Drop(arr.into(), i64::from(n))),
+            (Val::Atom(Atom::Num(n)), Val::Array(arr)) => Ok(arr.drop(i64::from(n))),
             (w, x) => rt_error(
                 format!(
                     "Attempted to drop {} items from {}",
@@ -406,8 +526,77 @@ impl Runtime {
             }
         }
     }
+    pub fn repeat(&self, f: Val, g: Val, w: Option<Val>, mut x: Val, span: &Span) -> RuntimeResult {
+        let step: Val = match (w.clone(), f) {
+            (Some(w), Val::Atom(Atom::Function(f))) => Function::from(Fork {
+                left: w,
+                center: f,
+                right: RuneOp::Laguz.into(),
+            })
+            .into(),
+            (_, f) => f,
+        };
+        match g {
+            Val::Atom(Atom::Function(condition)) => loop {
+                let holds = if let Some(w) = &w {
+                    self.eval_bin_function(condition.clone(), w.clone(), x.clone(), span)?
+                } else {
+                    self.eval_un_function(condition.clone(), x.clone(), span)?
+                };
+                match holds {
+                    Val::Atom(Atom::Num(n)) if n == 0 => return Ok(x),
+                    Val::Atom(Atom::Num(_)) => x = self.eval_un(step.clone(), x, span)?,
+                    val => {
+                        return rt_error(
+                            format!(
+                                "Repeat condition must return a number, but it returned {}",
+                                val.type_name()
+                            ),
+                            span,
+                        )
+                    }
+                }
+            },
+            Val::Atom(Atom::Num(n)) => {
+                let step = if n < 0 {
+                    match step {
+                        Val::Atom(Atom::Function(f)) => f.inverse(span)?.into(),
+                        f => return rt_error(format!("{} has no inverse", f.type_name()), span),
+                    }
+                } else {
+                    step
+                };
+                if n.is_infinite() {
+                    loop {
+                        let next = self.eval_un(step.clone(), x.clone(), span)?;
+                        if next.matches(&x)? {
+                            return Ok(next);
+                        }
+                        x = next;
+                    }
+                }
+                if n.floor() != n {
+                    return rt_error(
+                        format!("Repeat count must be an integer, but it is {}", n),
+                        span,
+                    );
+                }
+                for _ in 0..i64::from(n).unsigned_abs() {
+                    x = self.eval_un(step.clone(), x, span)?;
+                }
+                Ok(x)
+            }
+            g => rt_error(
+                format!(
+                    "Repeat count must be a number or function, but it is {}",
+                    g.type_name()
+                ),
+                span,
+            ),
+        }
+    }
     pub fn each_un(&self, op: Val, x: Val, span: &Span) -> RuntimeResult<Array> {
-        match x {
+        match pairs_view(x) {
             Val::Array(arr) => Ok(Array::Each(
                 LazyEach {
                     zip: ZipForm::Un(arr),
@@ -425,7 +614,7 @@ impl Runtime {
     }
 
     pub fn each_bin(&self, op: Val, w: Val, x: Val, span: &Span) -> RuntimeResult<Array> {
-        match ZipForm::bin(w, x) {
+        match ZipForm::bin(pairs_view(w), pairs_view(x)) {
             Ok(zip) => Ok(Array::Each(
                 LazyEach {
                     zip,
@@ -714,3 +903,119 @@ pub fn deduplicate(x: Val, span: &Span) -> RuntimeResult<Array> {
 pub fn rt_error<T>(message: impl Into<String>, span: &Span) -> RuntimeResult<T> {
     Err(RuntimeError::new(message, span.clone()))
 }
+
+/// Create a new temporary directory
+#[cfg(test)]
+pub fn test_dir() -> std::path::PathBuf {
+    use std::{
+        env, fs, process,
+        sync::atomic::{AtomicUsize, Ordering},
+    };
+
+    static DIRS: AtomicUsize = AtomicUsize::new(0);
+    let dir = env::temp_dir().join(format!(
+        "seidr-test-{}-{}",
+        process::id(),
+        DIRS.fetch_add(1, Ordering::Relaxed)
+    ));
+    fs::create_dir_all(&dir).unwrap();
+    dir
+}
+
+/// Evaluate files written to a new temporary directory
+///
+/// Gives the formatted value of the first file's last expression,
+/// or the message of the first error.
+#[cfg(test)]
+pub fn eval_test_files(files: &[(&str, &str)]) -> Result<String, String> {
+    eval_test_with(Runtime::default(), files)
+}
+
+/// Like [`eval_test_files`], but evaluating in the given runtime
+#[cfg(test)]
+pub fn eval_test_with(rt: Runtime, files: &[(&str, &str)]) -> Result<String, String> {
+    use std::fs;
+
+    use crate::{ast::Item, parse::parse};
+
+    let dir = test_dir();
+    for (name, code) in files {
+        fs::write(dir.join(name), code).unwrap();
+    }
+    let path = dir.join(files[0].0);
+    let result = (|| {
+        let code = fs::read_to_string(&path).unwrap();
+        let items = parse(&code, &path).map_err(RuntimeError::from)?;
+        let mut builder = TreeBuilder::default();
+        let mut val = Val::from(Array::empty());
+        for item in items {
+            if let Item::Expr(expr) = item {
+                let (node, _) = builder
+                    .build(&expr)
+                    .map_err(|problems| RuntimeError::from(problems.into_iter().next().unwrap()))?;
+                val = node.eval(&rt)?;
+            }
+        }
+        val.as_string()
+    })()
+    .map_err(|e| e.message);
+    let _ = fs::remove_dir_all(&dir);
+    result
+}
+
+/// Evaluate code, giving the formatted value of its last expression
+#[cfg(test)]
+pub fn eval_test(code: &str) -> Result<String, String> {
+    eval_test_files(&[("main.sdr", code)])
+}
+
+#[test]
+fn repeat() {
+    assert_eq!(eval_test("F ← ᛄ(2×ᛚ)3\nF 1").unwrap(), "8");
+    assert_eq!(eval_test("F ← ᛄ(2×ᛚ)‾2\nF 8").unwrap(), "2");
+    assert_eq!(eval_test("F ← ᛄ(ᛚ÷ᛜ2)(ᛚ>ᛜ10)\nF 100").unwrap(), "6.25");
+    assert_eq!(eval_test("F ← ᛄ(ᛚ⎣ᛜ5)∞\nF 100").unwrap(), "5");
+    assert_eq!(eval_test("F ← ᛄ᛭2\n3 F 1").unwrap(), "7");
+}
+
+#[test]
+fn closures() {
+    // Literals see later changes to the scope they were created in
+    assert_eq!(eval_test("x ← 1\nF ← ⦑x ᛭ ᚭ⦒\nx ↩ 10\nF 1").unwrap(), "11");
+    // Inner literals capture their caller's bindings
+    assert_eq!(eval_test("G ← ⦑n ← ᚭ × 2\n⦑ᚭ ᛭ n⦒ 1⦒\nG 5").unwrap(), "11");
+    // Literals outlive the scope that created them
+    assert_eq!(
+        eval_test("ns ← ⦃ n ← 3, F ← ⦑ᚭ ᛭ n⦒ ⦄\nns.F 4").unwrap(),
+        "7"
+    );
+    // Each call gets its own bindings
+    assert_eq!(
+        eval_test("F ← ⦑n ← ᚭ\n⦑n⦒ 0⦒\n⟨F 1, F 2⟩").unwrap(),
+        "⟨1 2⟩"
+    );
+}
+
+#[test]
+fn modified_assignment() {
+    assert_eq!(eval_test("a ← 1\na ᛭↩ 5\na").unwrap(), "6");
+    assert_eq!(eval_test("l ← ⟨1, 2⟩\nl ᛃ↩\nl").unwrap(), "⟨2 1⟩");
+    assert_eq!(eval_test("n ← 2\nF ← ⦑n ×↩ ᚭ⦒\nF 5\nn").unwrap(), "10");
+}
+
+#[test]
+fn destructuring() {
+    assert_eq!(
+        eval_test("⟨a, ⟨b, c⟩⟩ ← ⟨1, ⟨2, 3⟩⟩\n⟨c, b, a⟩").unwrap(),
+        "⟨3 2 1⟩"
+    );
+    assert_eq!(eval_test("⟨a, b⟩ ← \"hi\"\nb").unwrap(), "'i'");
+    assert_eq!(
+        eval_test("⟨a, b⟩ ← ⟨1, 2, 3⟩").unwrap_err(),
+        "Cannot destructure an array of length 3 into 2 names"
+    );
+    assert_eq!(
+        eval_test("⟨a, b⟩ ← 5").unwrap_err(),
+        "Cannot destructure number"
+    );
+}
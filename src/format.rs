@@ -3,7 +3,7 @@ use std::fmt;
 use crate::{
     array::Array,
     error::RuntimeResult,
-    lex::{digit_or_inf, ident_body_char, ident_head_char},
+    lex::{ident_body_char, ident_head_char, number_head_char},
     value::{Atom, Val},
 };
 
@@ -39,11 +39,11 @@ impl<'w> Formatter<'w> {
         }
         self.indent_queue = 0;
 
-        if self.prev_alphanum && s.starts_with(|c| ident_head_char(c) || digit_or_inf(c)) {
+        if self.prev_alphanum && s.starts_with(|c| ident_head_char(c) || number_head_char(c)) {
             write!(self.writer, " ").unwrap_or_else(|e| panic!("{}", e));
         }
 
-        self.prev_alphanum = s.ends_with(|c| ident_body_char(c) || digit_or_inf(c));
+        self.prev_alphanum = s.ends_with(|c| ident_body_char(c) || number_head_char(c));
 
         write!(self.writer, "{}", s).unwrap_or_else(|e| panic!("{}", e));
     }
@@ -67,6 +67,7 @@ impl<'w> Formatter<'w> {
         self.indent_queue = self.indent_queue.saturating_sub(delta);
     }
     pub fn newline(&mut self) {
+        self.indent_queue = 0;
         self.display('\n');
         self.indent_queue = self.indent;
     }
@@ -1,18 +1,39 @@
-use std::fmt;
+use std::{fmt, rc::Rc};
 
 use crate::{
     cwt::ValNode,
     error::RuntimeResult,
+    eval::rt_error,
     format::{Format, Formatter},
+    lex::Span,
     op::*,
     rcview::RcView,
-    value::Val,
+    runtime::Runtime,
+    value::{Atom, Val},
 };
 
+/// The body of a function or modifier literal along with the runtime it was created in
+///
+/// A closure bound to a name in the scope it captures, such as a named
+/// recursive function, forms a reference cycle through that scope, so the
+/// scope is never freed. The runtime is held strongly anyway so that closures
+/// can outlive the call that created them.
+#[derive(Clone, PartialEq, Eq, PartialOrd, Ord)]
+pub struct Closure {
+    pub nodes: RcView<ValNode>,
+    pub rt: Runtime,
+}
+
+impl fmt::Debug for Closure {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        self.nodes.fmt(f)
+    }
+}
+
 #[derive(Clone, PartialEq, Eq, PartialOrd, Ord)]
 pub enum Modifier<R> {
     Rune(R),
-    Nodes(RcView<ValNode>),
+    Nodes(Rc<Closure>),
 }
 
 impl<R> fmt::Debug for Modifier<R>
@@ -24,9 +45,9 @@ where
     }
 }
 
-impl<R> From<RcView<ValNode>> for Modifier<R> {
-    fn from(nodes: RcView<ValNode>) -> Self {
-        Modifier::Nodes(nodes)
+impl<R> From<Rc<Closure>> for Modifier<R> {
+    fn from(closure: Rc<Closure>) -> Self {
+        Modifier::Nodes(closure)
     }
 }
 
@@ -157,7 +178,7 @@ impl Format for Fork {
 #[derive(Clone, PartialEq, Eq, PartialOrd, Ord)]
 pub enum Function {
     Op(Op),
-    Nodes(RcView<ValNode>),
+    Nodes(Rc<Closure>),
     UnMod(Box<UnModded>),
     BinMod(Box<BinModded>),
     Atop(Box<Atop>),
@@ -175,6 +196,149 @@ impl Function {
             Function::Fork(fork) => fork.center.type_name(),
         }
     }
+    /// Get the function that undoes this one
+    pub fn inverse(&self, span: &Span) -> RuntimeResult<Function> {
+        Ok(match self {
+            Function::Op(Op::Pervasive(Pervasive::Math(math))) => match math {
+                MathOp::Add | MathOp::Sub | MathOp::Div => self.clone(),
+                MathOp::Pow => MathOp::Log.into(),
+                MathOp::Log => MathOp::Pow.into(),
+                MathOp::Sqrt => fork(identity(), MathOp::Pow, constant(2i64)),
+                MathOp::Sin => MathOp::Asin.into(),
+                MathOp::Cos => MathOp::Acos.into(),
+                MathOp::Tan => MathOp::Atan.into(),
+                MathOp::Asin => MathOp::Sin.into(),
+                MathOp::Acos => MathOp::Cos.into(),
+                MathOp::Atan => MathOp::Tan.into(),
+                _ => return self.no_inverse(span),
+            },
+            Function::Op(Op::Rune(RuneOp::Laguz | RuneOp::Jera | RuneOp::Uruz)) => self.clone(),
+            Function::Atop(atop) => Atop {
+                f: atop.g.inverse(span)?,
+                g: atop.f.inverse(span)?,
+            }
+            .into(),
+            Function::Fork(fork) => {
+                if let Some(c) = constant_of(&fork.left) {
+                    // c F (G x)
+                    let solved =
+                        solve_right(&fork.center, c).map_or_else(|| self.no_inverse(span), Ok)?;
+                    atop(fork.right.inverse(span)?, solved)
+                } else if let Some(c) = constant_of(&Val::from(fork.right.clone())) {
+                    // (L x) F c
+                    let left = match &fork.left {
+                        Val::Atom(Atom::Function(left)) => left,
+                        _ => return self.no_inverse(span),
+                    };
+                    let solved =
+                        solve_left(&fork.center, c).map_or_else(|| self.no_inverse(span), Ok)?;
+                    atop(left.inverse(span)?, solved)
+                } else {
+                    return self.no_inverse(span);
+                }
+            }
+            Function::UnMod(un_mod) => match (&un_mod.m, &un_mod.f) {
+                (UnMod::Rune(RuneUnMod::Ear), Val::Atom(Atom::Function(f))) => f.clone(),
+                _ => return self.no_inverse(span),
+            },
+            Function::BinMod(bin_mod) => match (&bin_mod.m, &bin_mod.f, &bin_mod.g) {
+                (
+                    BinMod::Rune(RuneBinMod::Haglaz),
+                    Val::Atom(Atom::Function(f)),
+                    Val::Atom(Atom::Function(g)),
+                ) => Atop {
+                    f: g.inverse(span)?,
+                    g: f.inverse(span)?,
+                }
+                .into(),
+                _ => return self.no_inverse(span),
+            },
+            _ => return self.no_inverse(span),
+        })
+    }
+    fn no_inverse<T>(&self, span: &Span) -> RuntimeResult<T> {
+        rt_error(format!("{} has no inverse", self.as_string()?), span)
+    }
+}
+
+/// Get the value of a fork tine that ignores its arguments
+fn constant_of(tine: &Val) -> Option<&Val> {
+    match tine {
+        Val::Atom(Atom::Function(Function::UnMod(un_mod)))
+            if un_mod.m == UnMod::Rune(RuneUnMod::Ingwaz) =>
+        {
+            Some(&un_mod.f)
+        }
+        Val::Atom(Atom::Function(_)) => None,
+        val => Some(val),
+    }
+}
+
+/// Get the function that solves `c F x = y` for `x`
+fn solve_right(f: &Function, c: &Val) -> Option<Function> {
+    let math = match f {
+        Function::Op(Op::Pervasive(Pervasive::Math(math))) => *math,
+        _ => return None,
+    };
+    let c = c.clone();
+    Some(match math {
+        MathOp::Add => fork(identity(), MathOp::Sub, constant(c)),
+        MathOp::Mul => fork(identity(), MathOp::Div, constant(c)),
+        MathOp::Sub | MathOp::Div => fork(c, math, RuneOp::Laguz),
+        MathOp::Pow => fork(identity(), MathOp::Log, constant(c)),
+        MathOp::Log => fork(identity(), MathOp::Sqrt, constant(c)),
+        MathOp::Sqrt => fork(identity(), MathOp::Pow, constant(c)),
+        _ => return None,
+    })
+}
+
+/// Get the function that solves `x F c = y` for `x`
+fn solve_left(f: &Function, c: &Val) -> Option<Function> {
+    let math = match f {
+        Function::Op(Op::Pervasive(Pervasive::Math(math))) => *math,
+        _ => return None,
+    };
+    let c = c.clone();
+    Some(match math {
+        MathOp::Add => fork(identity(), MathOp::Sub, constant(c)),
+        MathOp::Sub => fork(identity(), MathOp::Add, constant(c)),
+        MathOp::Mul => fork(identity(), MathOp::Div, constant(c)),
+        MathOp::Div => fork(identity(), MathOp::Mul, constant(c)),
+        MathOp::Pow => fork(c, MathOp::Sqrt, RuneOp::Laguz),
+        MathOp::Log => fork(c, MathOp::Pow, RuneOp::Laguz),
+        MathOp::Sqrt => fork(c, MathOp::Log, RuneOp::Laguz),
+        _ => return None,
+    })
+}
+
+fn fork(left: Val, center: impl Into<Function>, right: impl Into<Function>) -> Function {
+    Fork {
+        left,
+        center: center.into(),
+        right: right.into(),
+    }
+    .into()
+}
+
+fn identity() -> Val {
+    Function::from(RuneOp::Laguz).into()
+}
+
+fn constant(val: impl Into<Val>) -> Function {
+    UnModded {
+        m: RuneUnMod::Ingwaz.into(),
+        f: val.into(),
+    }
+    .into()
+}
+
+/// Compose two functions, skipping identities
+fn atop(f: Function, g: Function) -> Function {
+    match (f, g) {
+        (Function::Op(Op::Rune(RuneOp::Laguz)), g) => g,
+        (f, Function::Op(Op::Rune(RuneOp::Laguz))) => f,
+        (f, g) => Atop { f, g }.into(),
+    }
 }
 
 impl<O> From<O> for Function
@@ -186,6 +350,18 @@ where
     }
 }
 
+impl From<Atop> for Function {
+    fn from(atop: Atop) -> Self {
+        Function::Atop(atop.into())
+    }
+}
+
+impl From<Fork> for Function {
+    fn from(fork: Fork) -> Self {
+        Function::Fork(fork.into())
+    }
+}
+
 impl From<UnModded> for Function {
     fn from(m: UnModded) -> Self {
         Function::UnMod(m.into())
@@ -202,7 +378,7 @@ impl fmt::Debug for Function {
     fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
         match self {
             Function::Op(op) => op.fmt(f),
-            Function::Nodes(nodes) => nodes.fmt(f),
+            Function::Nodes(closure) => closure.fmt(f),
             Function::UnMod(un) => un.fmt(f),
             Function::BinMod(bin) => bin.fmt(f),
             Function::Atop(atop) => atop.f.fmt(f),
@@ -229,3 +405,26 @@ impl Format for Function {
         }
     }
 }
+
+#[test]
+fn root_inverses() {
+    use crate::eval::eval_test;
+    // 3 •Sqrt 8 is 2
+    assert_eq!(eval_test("ᛠ(ᛚ•Sqrtᛜ8) 2").unwrap(), "3");
+    assert_eq!(eval_test("ᛠ(3•Sqrtᛚ) 2").unwrap(), "8");
+    assert_eq!(eval_test("ᛠ(ᛚ*ᛜ2) 9").unwrap(), "3");
+    assert_eq!(eval_test("ᛠ(2*ᛚ) 8").unwrap(), "3");
+}
+
+#[test]
+fn undo() {
+    use crate::eval::eval_test;
+    assert_eq!(eval_test("ᛠ(ᛚ᛭ᛜ3) 10").unwrap(), "7");
+    assert_eq!(eval_test("ᛠ(2×ᛚ) 10").unwrap(), "5");
+    assert_eq!(eval_test("ᛠ(10-ᛚ) 3").unwrap(), "7");
+    assert_eq!(eval_test("ᛠᛃ ⟨1, 2, 3⟩").unwrap(), "⟨3 2 1⟩");
+    assert_eq!(eval_test("ᛠ(ᚻᛃ(ᛚ᛭ᛜ1)) ⟨1, 2⟩").unwrap(), "⟨1 0⟩");
+    assert!(eval_test("ᛠ(ᛚᛁᛜ2) 1")
+        .unwrap_err()
+        .ends_with("has no inverse"));
+}
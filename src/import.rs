@@ -0,0 +1,120 @@
+//! Importing other files
+//!
+//! `•Import "path.sdr"` evaluates a file in its own top-level scope and gives
+//! a namespace of the names it binds. Paths are relative to the importing
+//! file. Each file is only evaluated once, and later imports of it give the
+//! same namespace.
+
+use std::{
+    collections::HashMap,
+    fs,
+    path::{Path, PathBuf},
+};
+
+use crate::{
+    ast::Item,
+    cwt::TreeBuilder,
+    error::{Problem, RuntimeError, RuntimeResult},
+    eval::rt_error,
+    io::path_arg,
+    lex::Span,
+    namespace::Namespace,
+    parse::parse,
+    runtime::Runtime,
+    value::Val,
+};
+
+#[derive(Default)]
+pub struct Imports {
+    /// The namespaces of files that have been imported
+    cache: HashMap<PathBuf, Val>,
+    /// The files currently being imported
+    stack: Vec<PathBuf>,
+}
+
+impl Runtime {
+    pub fn import(&self, x: Val, span: &Span) -> RuntimeResult {
+        let path = span
+            .file
+            .parent()
+            .unwrap_or_else(|| Path::new(""))
+            .join(path_arg(&x, span)?);
+        let key = fs::canonicalize(&path).map_err(|e| {
+            RuntimeError::new(
+                format!("Unable to import `{}`: {}", path.to_string_lossy(), e),
+                span.clone(),
+            )
+        })?;
+        if let Some(ns) = self.imports().borrow().cache.get(&key) {
+            return Ok(ns.clone());
+        }
+        if self.imports().borrow().stack.contains(&key) {
+            return rt_error(
+                format!("Circular import of `{}`", path.to_string_lossy()),
+                span,
+            );
+        }
+        self.imports().borrow_mut().stack.push(key.clone());
+        let ns = self.eval_file(&path, span);
+        self.imports().borrow_mut().stack.pop();
+        let ns = ns.map_err(|e| e.trace_span(span))?;
+        self.imports().borrow_mut().cache.insert(key, ns.clone());
+        Ok(ns)
+    }
+    fn eval_file(&self, path: &Path, span: &Span) -> RuntimeResult {
+        let code = fs::read_to_string(path).map_err(|e| {
+            RuntimeError::new(
+                format!("Unable to read `{}`: {}", path.to_string_lossy(), e),
+                span.clone(),
+            )
+        })?;
+        let items = parse(&code, path)?;
+        let mut builder = TreeBuilder::default();
+        let mut nodes = Vec::new();
+        for item in items {
+            if let Item::Expr(expr) = item {
+                let (node, _) = builder.build(&expr).map_err(|problems| {
+                    let error = problems.into_iter().find(Problem::prevents_compilation);
+                    RuntimeError::from(error.expect("build failed without an error"))
+                })?;
+                nodes.push(node);
+            }
+        }
+        let rt = self.fresh();
+        rt.eval_nodes(nodes)?;
+        Ok(Namespace::from_scope(&rt, &builder.fields(), span)?.into())
+    }
+}
+
+#[test]
+fn imports() {
+    use crate::eval::eval_test_files;
+    let lib = "x ← 1\nF ← ⦑ᚭ ᛭ x⦒";
+    assert_eq!(
+        eval_test_files(&[
+            ("main.sdr", "lib ← •Import \"lib.sdr\"\nlib.F 2"),
+            ("lib.sdr", lib)
+        ])
+        .unwrap(),
+        "3"
+    );
+    // Each file is only evaluated once
+    assert_eq!(
+        eval_test_files(&[
+            (
+                "main.sdr",
+                "(•Import \"lib.sdr\").r ≡ (•Import \"lib.sdr\").r"
+            ),
+            ("lib.sdr", "r ← •Rand 0"),
+        ])
+        .unwrap(),
+        "1"
+    );
+    assert!(eval_test_files(&[
+        ("main.sdr", "•Import \"a.sdr\""),
+        ("a.sdr", "b ← •Import \"b.sdr\""),
+        ("b.sdr", "a ← •Import \"a.sdr\""),
+    ])
+    .unwrap_err()
+    .starts_with("Circular import of"));
+}
@@ -0,0 +1,247 @@
+//! Evaluation of file system operators
+
+use std::{
+    cell::{Cell, RefCell},
+    fmt,
+    fs::{self, OpenOptions},
+    io::{self, BufRead, Write},
+    path::{Path, PathBuf},
+};
+
+use crate::{
+    array::Array,
+    error::{RuntimeError, RuntimeResult},
+    eval::rt_error,
+    format::Format,
+    lex::Span,
+    value::Val,
+};
+
+/// Get a path from a string value
+pub fn path_arg(val: &Val, span: &Span) -> RuntimeResult<PathBuf> {
+    match val.as_text()? {
+        Some(path) => Ok(path.into()),
+        None => rt_error(
+            format!("{} cannot be used as a path", val.type_name()),
+            span,
+        ),
+    }
+}
+
+fn io_error(action: &str, path: &Path, e: io::Error, span: &Span) -> RuntimeError {
+    RuntimeError::new(
+        format!("Unable to {} `{}`: {}", action, path.to_string_lossy(), e),
+        span.clone(),
+    )
+}
+
+pub fn read_file(x: &Val, span: &Span) -> RuntimeResult {
+    let path = path_arg(x, span)?;
+    let s = fs::read_to_string(&path).map_err(|e| io_error("read", &path, e, span))?;
+    Ok(Array::string(s).into())
+}
+
+pub fn read_lines(x: &Val, span: &Span) -> RuntimeResult {
+    let path = path_arg(x, span)?;
+    let s = fs::read_to_string(&path).map_err(|e| io_error("read", &path, e, span))?;
+    Ok(Array::concrete(s.lines().map(Array::string)).into())
+}
+
+pub fn read_bytes(x: &Val, span: &Span) -> RuntimeResult {
+    let path = path_arg(x, span)?;
+    let bytes = fs::read(&path).map_err(|e| io_error("read", &path, e, span))?;
+    Ok(Array::concrete(bytes.into_iter().map(i64::from)).into())
+}
+
+pub fn write_file(w: &Val, x: Val, append: bool, span: &Span) -> RuntimeResult {
+    let path = path_arg(w, span)?;
+    let text = match x.as_text()? {
+        Some(text) => text,
+        None => {
+            return rt_error(
+                format!("{} cannot be written to a file", x.type_name()),
+                span,
+            )
+        }
+    };
+    OpenOptions::new()
+        .create(true)
+        .write(true)
+        .append(append)
+        .truncate(!append)
+        .open(&path)
+        .and_then(|mut file| file.write_all(text.as_bytes()))
+        .map_err(|e| io_error("write", &path, e, span))?;
+    Ok(x)
+}
+
+pub fn list_dir(x: &Val, span: &Span) -> RuntimeResult {
+    let path = path_arg(x, span)?;
+    let mut names = fs::read_dir(&path)
+        .and_then(|entries| {
+            entries
+                .map(|entry| Ok(entry?.file_name().to_string_lossy().into_owned()))
+                .collect::<io::Result<Vec<String>>>()
+        })
+        .map_err(|e| io_error("list", &path, e, span))?;
+    names.sort();
+    Ok(Array::concrete(names.into_iter().map(Array::string)).into())
+}
+
+/// Write a value to stdout
+///
+/// Strings are written as their text, and other values are formatted.
+pub fn print(x: Val, newline: bool, span: &Span) -> RuntimeResult {
+    let text = match x.as_text()? {
+        Some(text) => text,
+        None => x.as_string()?,
+    };
+    let mut stdout = io::stdout().lock();
+    let result = if newline {
+        writeln!(stdout, "{}", text)
+    } else {
+        write!(stdout, "{}", text).and_then(|_| stdout.flush())
+    };
+    result.map_err(|e| {
+        RuntimeError::new(format!("Unable to write to stdout: {}", e), span.clone())
+    })?;
+    Ok(x)
+}
+
+pub fn exists(x: &Val, span: &Span) -> RuntimeResult {
+    Ok(path_arg(x, span)?.exists().into())
+}
+
+/// The lines of standard input, read as they are needed
+#[derive(Default)]
+pub struct LazyStdin {
+    /// The input to read instead of standard input
+    input: RefCell<Option<Box<dyn BufRead>>>,
+    lines: RefCell<Vec<Val>>,
+    done: Cell<bool>,
+}
+
+impl fmt::Debug for LazyStdin {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "<stdin>")
+    }
+}
+
+/// There is only one standard input, so all of its arrays are equal
+impl PartialEq for LazyStdin {
+    fn eq(&self, _: &Self) -> bool {
+        true
+    }
+}
+
+impl Eq for LazyStdin {}
+
+impl LazyStdin {
+    /// Read from the given input instead of standard input
+    pub fn new(input: impl BufRead + 'static) -> Self {
+        LazyStdin {
+            input: RefCell::new(Some(Box::new(input))),
+            ..Default::default()
+        }
+    }
+    fn read<F, T>(&self, f: F) -> RuntimeResult<T>
+    where
+        F: FnOnce(&mut dyn BufRead) -> io::Result<T>,
+    {
+        let result = match &mut *self.input.borrow_mut() {
+            Some(input) => f(input),
+            None => f(&mut io::stdin().lock()),
+        };
+        result.map_err(|e| {
+            self.done.set(true);
+            RuntimeError::new(format!("Unable to read stdin: {}", e), Span::dud())
+        })
+    }
+    pub fn get(&self, index: usize) -> RuntimeResult<Option<Val>> {
+        while !self.done.get() && self.lines.borrow().len() <= index {
+            let mut line = String::new();
+            if self.read(|input| input.read_line(&mut line))? == 0 {
+                self.done.set(true);
+            } else {
+                if line.ends_with('\n') {
+                    line.pop();
+                    if line.ends_with('\r') {
+                        line.pop();
+                    }
+                }
+                self.lines.borrow_mut().push(Array::string(line).into());
+            }
+        }
+        Ok(self.lines.borrow().get(index).cloned())
+    }
+    /// Read the rest of standard input unchanged
+    ///
+    /// Lines already read for `•StdinLines` are not included,
+    /// but the lines read here are still added to it.
+    pub fn read_all(&self) -> RuntimeResult<String> {
+        let mut text = String::new();
+        if !self.done.get() {
+            self.read(|input| input.read_to_string(&mut text))?;
+            self.done.set(true);
+        }
+        let mut lines = self.lines.borrow_mut();
+        lines.extend(text.lines().map(|line| Array::string(line).into()));
+        Ok(text)
+    }
+}
+
+#[test]
+fn files() {
+    use crate::eval::{eval_test, test_dir};
+    let dir = test_dir();
+    let path = |name: &str| format!("{:?}", dir.join(name));
+    let (a, b) = (path("a.txt"), path("b.txt"));
+    let code = format!("{a} •WriteFile \"x\\n\"\n{a} •AppendFile \"ᚠ\"\n•ReadFile {a}");
+    assert_eq!(eval_test(&code).unwrap(), "\"x\\nᚠ\"");
+    assert_eq!(
+        eval_test(&format!("•ReadLines {a}")).unwrap(),
+        "\"x\"\n\"ᚠ\"\n"
+    );
+    assert_eq!(
+        eval_test(&format!("3ᚾ •ReadBytes {a}")).unwrap(),
+        "⟨120 10 225⟩"
+    );
+    // Writing replaces the contents
+    let code = format!("{a} •WriteFile \"y\"\n•ReadFile {a}");
+    assert_eq!(eval_test(&code).unwrap(), "\"y\"");
+    assert_eq!(eval_test(&format!("•Exists {a}")).unwrap(), "1");
+    assert_eq!(eval_test(&format!("•Exists {b}")).unwrap(), "0");
+    assert!(eval_test(&format!("•ReadFile {b}"))
+        .unwrap_err()
+        .starts_with(&format!("Unable to read `{}`", dir.join("b.txt").display())));
+    let code = format!(
+        "{b} •WriteFile \"\"\n{} •WriteFile \"\"\n•ListDir {:?}",
+        path("C.txt"),
+        dir
+    );
+    assert_eq!(
+        eval_test(&code).unwrap(),
+        "\"C.txt\"\n\"a.txt\"\n\"b.txt\"\n"
+    );
+    std::fs::remove_dir_all(dir).unwrap();
+}
+
+#[test]
+fn args_and_stdin() {
+    use crate::{eval::eval_test_with, runtime::Runtime};
+    let eval = |input: &'static str, code| {
+        let rt = Runtime::default()
+            .with_args(vec!["a".into(), "ᚠ b".into()])
+            .with_stdin(input.as_bytes());
+        eval_test_with(rt, &[("main.sdr", code)]).unwrap()
+    };
+    assert_eq!(eval("", "•Args 0"), "\"a\"\n\"ᚠ b\"\n");
+    assert_eq!(eval("x\r\ny\n", "•Stdin 0"), "\"x\\r\\ny\\n\"");
+    assert_eq!(eval("x\r\ny", "2ᚾ •StdinLines 0"), "\"x\"\n\"y\"\n");
+    // Stdin only gives the lines that have not been read yet
+    let code = "a ← 0ᛈ •StdinLines 0\n⟨a, •Stdin 0, 3ᚾ •StdinLines 0⟩";
+    assert_eq!(
+        eval("x\ny\nz\n", code),
+        "\"x\"\n\"y\\nz\\n\"\n\"x\"\n\"y\"\n\"z\"\n\n"
+    );
+}
@@ -0,0 +1,282 @@
+//! Conversion between values and JSON text
+//!
+//! Arrays are JSON lists, except for arrays of characters, which are strings.
+//! Maps and namespaces are objects. Decoded objects are maps with string keys,
+//! `true` and `false` are `1` and `0`, and `null` is an empty array.
+
+use std::{iter::Peekable, str::Chars};
+
+use crate::{
+    array::Array,
+    error::RuntimeResult,
+    eval::rt_error,
+    lex::Span,
+    map::Map,
+    num::Num,
+    value::{Atom, Val},
+};
+
+/// Encode a value as JSON text
+pub fn encode(val: &Val, span: &Span) -> RuntimeResult<String> {
+    let mut s = String::new();
+    encode_into(val, &mut s, span)?;
+    Ok(s)
+}
+
+fn encode_into(val: &Val, s: &mut String, span: &Span) -> RuntimeResult<()> {
+    match val {
+        Val::Atom(Atom::Num(num)) => match num {
+            Num::Int(i) => s.push_str(&i.to_string()),
+            Num::Float(f) if f.is_finite() => s.push_str(&f.to_string()),
+            num => return rt_error(format!("{} cannot be encoded as JSON", num), span),
+        },
+        Val::Atom(Atom::Char(c)) => encode_str(&c.to_string(), s),
+        Val::Atom(Atom::Map(map)) => {
+            s.push('{');
+            for (i, (key, value)) in map.iter().enumerate() {
+                if i > 0 {
+                    s.push(',');
+                }
+                match key.as_text()? {
+                    Some(key) => encode_str(&key, s),
+                    None => {
+                        return rt_error(
+                            format!("{} cannot be used as a JSON key", key.type_name()),
+                            span,
+                        )
+                    }
+                }
+                s.push(':');
+                encode_into(value, s, span)?;
+            }
+            s.push('}');
+        }
+        Val::Atom(Atom::Namespace(ns)) => {
+            s.push('{');
+            for (i, (name, value)) in ns.fields().enumerate() {
+                if i > 0 {
+                    s.push(',');
+                }
+                encode_str(name.as_ref(), s);
+                s.push(':');
+                encode_into(value, s, span)?;
+            }
+            s.push('}');
+        }
+        Val::Atom(atom) => {
+            return rt_error(
+                format!("A {} cannot be encoded as JSON", atom.type_name()),
+                span,
+            )
+        }
+        Val::Array(arr) if arr.len().is_none() => {
+            return rt_error("Unbounded arrays cannot be encoded as JSON", span)
+        }
+        Val::Array(arr) => match val.as_text()? {
+            Some(text) if arr.len() != Some(0) => encode_str(&text, s),
+            _ => {
+                s.push('[');
+                for (i, item) in arr.iter().enumerate() {
+                    if i > 0 {
+                        s.push(',');
+                    }
+                    encode_into(&*item?, s, span)?;
+                }
+                s.push(']');
+            }
+        },
+    }
+    Ok(())
+}
+
+fn encode_str(text: &str, s: &mut String) {
+    s.push('"');
+    for c in text.chars() {
+        match c {
+            '"' => s.push_str("\\\""),
+            '\\' => s.push_str("\\\\"),
+            '\n' => s.push_str("\\n"),
+            '\r' => s.push_str("\\r"),
+            '\t' => s.push_str("\\t"),
+            c if (c as u32) < 0x20 => s.push_str(&format!("\\u{:04x}", c as u32)),
+            c => s.push(c),
+        }
+    }
+    s.push('"');
+}
+
+/// Decode JSON text into a value
+pub fn decode(text: &str, span: &Span) -> RuntimeResult {
+    let mut decoder = Decoder {
+        chars: text.chars().peekable(),
+        span,
+    };
+    let val = decoder.value()?;
+    decoder.whitespace();
+    if decoder.chars.peek().is_some() {
+        return decoder.error("unexpected text after value");
+    }
+    Ok(val)
+}
+
+struct Decoder<'a> {
+    chars: Peekable<Chars<'a>>,
+    span: &'a Span,
+}
+
+impl<'a> Decoder<'a> {
+    fn error<T>(&self, message: &str) -> RuntimeResult<T> {
+        rt_error(format!("Invalid JSON: {}", message), self.span)
+    }
+    fn whitespace(&mut self) {
+        while self.chars.next_if(|c| c.is_whitespace()).is_some() {}
+    }
+    fn expect(&mut self, expected: char) -> RuntimeResult<()> {
+        self.whitespace();
+        match self.chars.next() {
+            Some(c) if c == expected => Ok(()),
+            _ => self.error(&format!("expected `{}`", expected)),
+        }
+    }
+    fn value(&mut self) -> RuntimeResult {
+        self.whitespace();
+        Ok(match self.chars.peek() {
+            Some('{') => {
+                self.chars.next();
+                let mut map = Map::default();
+                self.whitespace();
+                if self.chars.next_if_eq(&'}').is_none() {
+                    loop {
+                        self.expect('"')?;
+                        let key = self.string()?;
+                        self.expect(':')?;
+                        let value = self.value()?;
+                        map.insert(Array::string(key).into(), value, self.span)?;
+                        self.whitespace();
+                        match self.chars.next() {
+                            Some(',') => {}
+                            Some('}') => break,
+                            _ => return self.error("expected `,` or `}`"),
+                        }
+                    }
+                }
+                map.into()
+            }
+            Some('[') => {
+                self.chars.next();
+                let mut items = Vec::new();
+                self.whitespace();
+                if self.chars.next_if_eq(&']').is_none() {
+                    loop {
+                        items.push(self.value()?);
+                        self.whitespace();
+                        match self.chars.next() {
+                            Some(',') => {}
+                            Some(']') => break,
+                            _ => return self.error("expected `,` or `]`"),
+                        }
+                    }
+                }
+                Array::concrete(items).into()
+            }
+            Some('"') => {
+                self.chars.next();
+                Array::string(self.string()?).into()
+            }
+            Some(c) if c.is_ascii_alphabetic() => {
+                let mut word = String::new();
+                while let Some(c) = self.chars.next_if(char::is_ascii_alphabetic) {
+                    word.push(c);
+                }
+                match word.as_str() {
+                    "true" => 1i64.into(),
+                    "false" => 0i64.into(),
+                    "null" => Array::empty().into(),
+                    _ => return self.error(&format!("unknown word `{}`", word)),
+                }
+            }
+            Some(_) => {
+                let mut s = String::new();
+                while let Some(c) = self
+                    .chars
+                    .next_if(|c| c.is_ascii_digit() || "+-.eE".contains(*c))
+                {
+                    s.push(c);
+                }
+                match s.parse::<Num>() {
+                    Ok(num) if !s.is_empty() => num.into(),
+                    _ => return self.error("expected a value"),
+                }
+            }
+            None => return self.error("expected a value"),
+        })
+    }
+    /// Parse the rest of a string after the opening quote
+    fn string(&mut self) -> RuntimeResult<String> {
+        let mut s = String::new();
+        loop {
+            match self.chars.next() {
+                Some('"') => return Ok(s),
+                Some('\\') => match self.chars.next() {
+                    Some('n') => s.push('\n'),
+                    Some('r') => s.push('\r'),
+                    Some('t') => s.push('\t'),
+                    Some('b') => s.push('\u{8}'),
+                    Some('f') => s.push('\u{c}'),
+                    Some('u') => {
+                        let code = self.hex4()?;
+                        // Combine surrogate pairs
+                        let code = if (0xd800..0xdc00).contains(&code) {
+                            if self.chars.next() != Some('\\') || self.chars.next() != Some('u') {
+                                return self.error("unpaired surrogate");
+                            }
+                            let low = self.hex4()?;
+                            if !(0xdc00..0xe000).contains(&low) {
+                                return self.error("unpaired surrogate");
+                            }
+                            0x10000 + ((code - 0xd800) << 10) + (low - 0xdc00)
+                        } else {
+                            code
+                        };
+                        match char::from_u32(code) {
+                            Some(c) => s.push(c),
+                            None => return self.error("invalid unicode escape"),
+                        }
+                    }
+                    Some(c @ ('"' | '\\' | '/')) => s.push(c),
+                    _ => return self.error("invalid escape"),
+                },
+                Some(c) => s.push(c),
+                None => return self.error("unclosed string"),
+            }
+        }
+    }
+    fn hex4(&mut self) -> RuntimeResult<u32> {
+        let mut code = 0;
+        for _ in 0..4 {
+            match self.chars.next().and_then(|c| c.to_digit(16)) {
+                Some(d) => code = code * 16 + d,
+                None => return self.error("invalid unicode escape"),
+            }
+        }
+        Ok(code)
+    }
+}
+
+#[test]
+fn json_round_trip() {
+    use crate::eval::eval_test;
+    let code = r#"•ToJson •Json "{\"a\": [1, 2.5, \"x\\u00e9\"], \"b\": true, \"c\": null}""#;
+    assert_eq!(
+        eval_test(code).unwrap(),
+        r#""{\"a\":[1,2.5,\"xé\"],\"b\":1,\"c\":[]}""#
+    );
+    assert_eq!(
+        eval_test(r#"•Json "\"\\ud83d\\ude00\"""#).unwrap(),
+        "\"😀\""
+    );
+    assert_eq!(
+        eval_test(r#"•Json "\"\\ud800\\u0041\"""#).unwrap_err(),
+        "Invalid JSON: unpaired surrogate"
+    );
+}
@@ -46,6 +46,16 @@ where
     Ok(tokens)
 }
 
+/// Lex text that does not come from a source file
+///
+/// Escapes are not written back anywhere.
+pub fn lex_text<P>(input: &str, file: P) -> CompileResult<Vec<Token>>
+where
+    P: AsRef<Path>,
+{
+    Lexer::new(input, file).lex()
+}
+
 #[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord)]
 pub enum Role {
     Value,
@@ -224,6 +234,12 @@ impl fmt::Display for Comment {
 
 #[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord)]
 pub enum ParamPlace {
+    /// The function being called
+    SelfFunction,
+    /// The unary modifier being called
+    SelfUnMod,
+    /// The binary modifier being called
+    SelfBinMod,
     X,
     W,
     F,
@@ -236,8 +252,18 @@ impl ParamPlace {
             ParamPlace::X | ParamPlace::W => Role::Function,
             ParamPlace::F => Role::UnModifier,
             ParamPlace::G => Role::BinModifier,
+            ParamPlace::SelfFunction | ParamPlace::SelfUnMod | ParamPlace::SelfBinMod => {
+                Role::Function
+            }
         }
     }
+    /// Check if this place refers to the enclosing function or modifier
+    pub const fn is_self(&self) -> bool {
+        matches!(
+            self,
+            ParamPlace::SelfFunction | ParamPlace::SelfUnMod | ParamPlace::SelfBinMod
+        )
+    }
 }
 
 #[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord)]
@@ -253,13 +279,18 @@ pub struct Param {
 }
 
 impl Param {
+    pub const SELF_FUNCTION: Self = Param::new(ParamPlace::SelfFunction, ParamForm::Function);
+    pub const SELF_UN_MOD: Self = Param::new(ParamPlace::SelfUnMod, ParamForm::Function);
+    pub const SELF_BIN_MOD: Self = Param::new(ParamPlace::SelfBinMod, ParamForm::Function);
     pub const fn new(place: ParamPlace, form: ParamForm) -> Self {
         Param { place, form }
     }
     pub const fn role(&self) -> Role {
-        match self.form {
-            ParamForm::Value => Role::Value,
-            ParamForm::Function => Role::Function,
+        match (self.place, self.form) {
+            (ParamPlace::SelfUnMod, _) => Role::UnModifier,
+            (ParamPlace::SelfBinMod, _) => Role::BinModifier,
+            (_, ParamForm::Value) => Role::Value,
+            (_, ParamForm::Function) => Role::Function,
         }
     }
 }
@@ -268,6 +299,9 @@ const W_PARAM_CHAR: char = 'ᚮ';
 const X_PARAM_CHAR: char = 'ᚭ';
 const F_PARAM_CHAR: char = 'ᚯ';
 const G_PARAM_CHAR: char = 'ᚬ';
+const SELF_FUNCTION_CHAR: char = 'ᚩ';
+const SELF_UN_MOD_CHAR: char = 'ᚪ';
+const SELF_BIN_MOD_CHAR: char = 'ᚫ';
 
 impl fmt::Debug for Param {
     fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
@@ -286,6 +320,9 @@ impl fmt::Display for Param {
             (ParamPlace::F, ParamForm::Function) => write!(f, "{}", F_PARAM_CHAR),
             (ParamPlace::G, ParamForm::Value) => write!(f, "᛬{}", G_PARAM_CHAR),
             (ParamPlace::G, ParamForm::Function) => write!(f, "{}", G_PARAM_CHAR),
+            (ParamPlace::SelfFunction, _) => write!(f, "{}", SELF_FUNCTION_CHAR),
+            (ParamPlace::SelfUnMod, _) => write!(f, "{}", SELF_UN_MOD_CHAR),
+            (ParamPlace::SelfBinMod, _) => write!(f, "{}", SELF_BIN_MOD_CHAR),
         }
     }
 }
@@ -311,8 +348,11 @@ pub enum TT {
     CloseAngleDot,
     OpenAngle,
     CloseAngle,
+    OpenCurly,
+    CloseCurly,
     // Misc
     Comma,
+    Dot,
     Whitespace,
     Newline,
     SuperscriptMinus,
@@ -368,6 +408,9 @@ impl fmt::Display for TT {
             TT::CloseAngleDot => '⦒'.fmt(f),
             TT::OpenAngle => '⟨'.fmt(f),
             TT::CloseAngle => '⟩'.fmt(f),
+            TT::OpenCurly => '⦃'.fmt(f),
+            TT::CloseCurly => '⦄'.fmt(f),
+            TT::Dot => '.'.fmt(f),
             TT::Op(op) => op.fmt(f),
             TT::UnMod(m) => m.fmt(f),
             TT::BinMod(m) => m.fmt(f),
@@ -641,11 +684,13 @@ impl Lexer {
                 ')' => self.token(TT::CloseParen),
                 '⦑' | '{' => self.token(TT::OpenAngleDot),
                 '⦒' | '}' => self.token(TT::CloseAngleDot),
+                '⦃' => self.token(TT::OpenCurly),
+                '⦄' => self.token(TT::CloseCurly),
+                '.' => self.token(TT::Dot),
                 '⟨' | '〈' | '[' => self.token(TT::OpenAngle),
                 '⟩' | '〉' | ']' => self.token(TT::CloseAngle),
                 ',' => self.token(TT::Comma),
                 '\n' => self.token(TT::Newline),
-                '∞' => self.token(TT::Num(Num::INFINIFY, "∞".into())),
                 '+' => self.token(MathOp::Add),
                 '「' => self.token(MathOp::Max),
                 '|' => self.token(MathOp::Mod),
@@ -662,6 +707,7 @@ impl Lexer {
                 }
                 '\\' => self.escape()?,
                 '‾' => self.negative_number()?,
+                SYSTEM_PREFIX => self.system()?,
                 W_PARAM_CHAR => self.token2(
                     '᛬',
                     Param::new(ParamPlace::W, ParamForm::Value),
@@ -674,6 +720,9 @@ impl Lexer {
                 ),
                 X_PARAM_CHAR => self.token(Param::new(ParamPlace::X, ParamForm::Value)),
                 G_PARAM_CHAR => self.token(Param::new(ParamPlace::G, ParamForm::Function)),
+                SELF_FUNCTION_CHAR => self.token(Param::SELF_FUNCTION),
+                SELF_UN_MOD_CHAR => self.token(Param::SELF_UN_MOD),
+                SELF_BIN_MOD_CHAR => self.token(Param::SELF_BIN_MOD),
                 '᛬' => match self.next() {
                     Some(X_PARAM_CHAR) => {
                         self.token(Param::new(ParamPlace::X, ParamForm::Function))
@@ -688,7 +737,7 @@ impl Lexer {
                 },
                 MULTI_LINE_COMMENT_OPEN => self.comment(MULTI_LINE_COMMENT_CLOSE, true),
                 SINGLE_LINE_COMMENT_CHAR | '#' => self.comment('\n', false),
-                c if digit_or_inf(c) => self.number(c, false)?,
+                c if number_head_char(c) => self.number(c, false)?,
                 c if ident_head_char(c) => {
                     let mut ident = String::from(c);
                     while let Some(c) = self.next_if(ident_body_char) {
@@ -738,7 +787,9 @@ impl Lexer {
             return self.error(CompileError::InvalidEscape(String::new()));
         };
         match c {
-            '8' => self.token(TT::Num(Num::INFINIFY, "∞".into())),
+            '8' => self.number('∞', false)?,
+            'P' => self.number('π', false)?,
+            'E' => self.number('ℯ', false)?,
             '-' => self.negative_number()?,
             '*' => self.comment('*', true),
             '1' => self.token(Param::new(ParamPlace::X, ParamForm::Value)),
@@ -749,6 +800,12 @@ impl Lexer {
             '6' => self.token(Param::new(ParamPlace::W, ParamForm::Function)),
             '7' => self.token(Param::new(ParamPlace::F, ParamForm::Value)),
             '9' => self.token(Param::new(ParamPlace::G, ParamForm::Value)),
+            'O' => self.token(Param::SELF_FUNCTION),
+            'C' => self.token(Param::SELF_UN_MOD),
+            'Q' => self.token(Param::SELF_BIN_MOD),
+            '.' => self.system()?,
+            '(' => self.token(TT::OpenCurly),
+            ')' => self.token(TT::CloseCurly),
             c => {
                 if let Some(op) = Op::from_escape(c) {
                     self.token(op);
@@ -766,8 +823,20 @@ impl Lexer {
         self.escaped = true;
         Ok(())
     }
+    fn system(&mut self) -> CompileResult {
+        let mut name = String::new();
+        while let Some(c) = self.next_if(ident_body_char) {
+            name.push(c);
+        }
+        if let Some(op) = Op::from_name(&name) {
+            self.token(op);
+            Ok(())
+        } else {
+            self.error(CompileError::UnknownSystemName(name))
+        }
+    }
     fn negative_number(&mut self) -> CompileResult {
-        if let Some(c) = self.next_if(digit_or_inf) {
+        if let Some(c) = self.next_if(number_head_char) {
             self.number(c, true)
         } else {
             self.error(CompileError::Expected("digit".into()))
@@ -775,32 +844,37 @@ impl Lexer {
     }
     fn number(&mut self, first: char, neg: bool) -> CompileResult {
         let neg = Num::from(if neg { -1i64 } else { 1 });
-        if first == '∞' {
-            self.token(TT::Num(Num::INFINIFY * neg, "‾∞".into()));
+        if let Some(constant) = num_constant(first) {
+            self.token(TT::Num(constant * neg, first.to_string().into()));
             return Ok(());
         }
+        if first == '0' {
+            if let Some(prefix) = self.next_if(|c| ['x', 'X', 'b', 'B', 'o', 'O'].contains(&c)) {
+                return self.radix_number(prefix.to_ascii_lowercase(), neg);
+            }
+        }
         let mut s = String::from(first);
-        while let Some(c) = self.next_if(|c| c.is_digit(10) || c == '_') {
+        while let Some(c) = self.next_if(|c| c.is_ascii_digit() || c == '_') {
             s.push(c);
         }
         if self.next_if(|c| c == '.').is_some() {
             s.push('.');
-            while let Some(c) = self.next_if(|c| c.is_digit(10) || c == '_') {
+            while let Some(c) = self.next_if(|c| c.is_ascii_digit() || c == '_') {
                 s.push(c);
             }
         }
         if s.ends_with('.') {
             return self.error(CompileError::InvalidNumber(s));
         }
-        if let Some(e) = self.next_if(|c| ['e', 'E'].contains(&c)) {
-            s.push(e);
+        if self.next_if(|c| ['e', 'E'].contains(&c)).is_some() {
+            s.push('e');
             if let Some(sign) = self.next_if(|c| ['+', '-', '‾'].contains(&c)) {
                 s.push(sign);
             }
-            while let Some(c) = self.next_if(ident_body_char) {
+            while let Some(c) = self.next_if(|c| c.is_ascii_digit() || c == '_') {
                 s.push(c);
             }
-            if !s.ends_with(|c: char| c.is_digit(10) || c == '_') {
+            if !s.ends_with(|c: char| c.is_ascii_digit() || c == '_') {
                 return self.error(CompileError::InvalidNumber(s));
             }
         }
@@ -811,6 +885,25 @@ impl Lexer {
         }
         Ok(())
     }
+    fn radix_number(&mut self, prefix: char, neg: Num) -> CompileResult {
+        let radix = match prefix {
+            'x' => 16,
+            'b' => 2,
+            _ => 8,
+        };
+        let mut s = format!("0{}", prefix);
+        while let Some(c) = self.next_if(ident_body_char) {
+            s.push(c);
+        }
+        let digits = s[2..].replace('_', "");
+        match i64::from_str_radix(&digits, radix) {
+            Ok(i) if !digits.starts_with(['+', '-']) => {
+                self.token(TT::Num(Num::Int(i) * neg, s.into()));
+                Ok(())
+            }
+            _ => self.error(CompileError::InvalidNumber(s)),
+        }
+    }
     fn char_literal(
         &mut self,
         delimeter: char,
@@ -865,19 +958,29 @@ impl BoolTake for bool {
 }
 
 pub fn ident_head_char(c: char) -> bool {
-    !digit_or_inf(c) && ident_body_char(c)
+    !number_head_char(c) && ident_body_char(c)
 }
 
 pub fn ident_body_char(c: char) -> bool {
-    c.is_alphanumeric() && !is_runic(c) || c == '_'
+    c.is_alphanumeric() && !is_runic(c) && num_constant(c).is_none() || c == '_'
 }
 
 fn is_runic(c: char) -> bool {
     ('ᚠ'..='ᛪ').contains(&c)
 }
 
-pub fn digit_or_inf(c: char) -> bool {
-    c.is_digit(10) || c == '∞'
+pub fn number_head_char(c: char) -> bool {
+    c.is_ascii_digit() || num_constant(c).is_some()
+}
+
+/// Get the value of a glyph that is a numeric constant
+pub fn num_constant(c: char) -> Option<Num> {
+    match c {
+        '∞' => Some(Num::INFINIFY),
+        'π' => Some(Num::PI),
+        'ℯ' => Some(Num::E),
+        _ => None,
+    }
 }
 
 #[derive(Clone)]
@@ -987,3 +1090,11 @@ impl<T> DerefMut for Sp<T> {
         &mut self.data
     }
 }
+
+#[test]
+fn number_literals_round_trip() {
+    let input = "0xFF ‾0b1010 0o17 1.5e3 ‾2e‾2 12_345_678 1.234_567_8 π ‾ℯ ∞";
+    let tokens = lex(input, "").unwrap();
+    let output: String = tokens.iter().map(|token| token.tt.to_string()).collect();
+    assert_eq!(input, output);
+}
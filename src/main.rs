@@ -1,38 +1,155 @@
 #![allow(unused, clippy::match_single_binding)]
 #![warn(unused_imports, unused_must_use, unreachable_patterns)]
 
-use std::fs::read_to_string;
+use std::{
+    fmt::Display,
+    fs::read_to_string,
+    io::{stderr, stdout, Stderr, Stdout, Write},
+    path::PathBuf,
+    process,
+};
 
+use clap::{Parser, Subcommand};
 use cwt::TreeBuilder;
 use runtime::Runtime;
 
-use crate::{ast::Item, eval::Eval, format::Format};
+use crate::{
+    ast::Item,
+    error::{RuntimeError, RuntimeResult},
+    eval::Eval,
+    format::Format,
+    value::Val,
+};
 
 mod array;
 mod ast;
+mod csv;
 mod cwt;
 mod error;
 mod eval;
 mod format;
 mod function;
+mod import;
+mod io;
+mod json;
 mod lex;
+mod map;
+mod namespace;
 mod num;
 mod op;
 mod parse;
 mod pervade;
+mod printf;
+mod random;
 mod rcview;
+mod re;
 mod runtime;
+mod system;
+mod text;
+mod time;
+mod under;
 mod value;
 
+#[derive(Parser)]
+#[clap(version)]
+struct Cli {
+    #[clap(subcommand)]
+    command: Option<Command>,
+}
+
+#[derive(Subcommand)]
+enum Command {
+    /// Run a script, only printing what it writes
+    Run {
+        /// Print each top-level expression and its value
+        #[clap(long)]
+        echo: bool,
+        /// The script to run
+        #[clap(parse(from_os_str))]
+        path: PathBuf,
+        /// Arguments given to the script
+        args: Vec<String>,
+    },
+}
+
 fn main() {
-    let path = "main.sdr";
+    let (path, args, echo) = match Cli::parse().command {
+        Some(Command::Run { echo, path, args }) => (path, args, echo),
+        None => ("main.sdr".into(), Vec::new(), true),
+    };
+    if !run(path, args, &mut Printer::std(echo)) {
+        process::exit(1);
+    }
+}
+
+/// Where the output of running a file goes
+///
+/// In echo mode, each top-level expression is printed along with its value
+/// and problems are printed to `out`. Otherwise, only problems are printed, to `err`.
+struct Printer<O, E> {
+    echo: bool,
+    out: O,
+    err: E,
+}
+
+impl Printer<Stdout, Stderr> {
+    fn std(echo: bool) -> Self {
+        Printer {
+            echo,
+            out: stdout(),
+            err: stderr(),
+        }
+    }
+}
 
+impl<O, E> Printer<O, E>
+where
+    O: Write,
+    E: Write,
+{
+    /// Print an expression before it is evaluated
+    fn expr(&mut self, expr: &dyn Display) {
+        if self.echo {
+            writeln!(self.out).unwrap();
+            writeln!(self.out, "    {}", expr).unwrap();
+        }
+    }
+    /// Print the value of an expression
+    fn value(&mut self, val: &Val) -> RuntimeResult<()> {
+        if self.echo {
+            writeln!(self.out, "{}", val.as_string()?).unwrap();
+        }
+        Ok(())
+    }
+    /// Print a problem that stops the file from running
+    fn problem(&mut self, message: &dyn Display) {
+        if self.echo {
+            writeln!(self.out, "{}", message).unwrap();
+        } else {
+            writeln!(self.err, "{}", message).unwrap();
+        }
+    }
+    /// Print an error from evaluating an expression
+    fn error(&mut self, error: &RuntimeError) {
+        if self.echo {
+            writeln!(self.out).unwrap();
+        }
+        self.problem(error);
+    }
+}
+
+/// Run a file, returning whether it succeeded
+fn run<O, E>(path: PathBuf, args: Vec<String>, printer: &mut Printer<O, E>) -> bool
+where
+    O: Write,
+    E: Write,
+{
     // Read in file
     let code = match read_to_string(&path) {
         Ok(code) => code,
         Err(e) => {
-            println!("{}", e);
-            return;
+            printer.problem(&e);
+            return false;
         }
     };
 
@@ -40,13 +157,13 @@ fn main() {
     let items = match parse::parse(&code, path) {
         Ok(exprs) => exprs,
         Err(e) => {
-            println!("{}", e);
-            return;
+            printer.problem(&e);
+            return false;
         }
     };
 
     let mut builder = TreeBuilder::default();
-    let rt = Runtime::default();
+    let rt = Runtime::default().with_args(args);
     let mut nodes = Vec::new();
     let mut errored = false;
     for item in items {
@@ -56,13 +173,13 @@ fn main() {
                 Ok((node, warnings)) => {
                     nodes.push((expr, node));
                     for warning in warnings {
-                        println!("{}", warning);
+                        printer.problem(&warning);
                     }
                 }
                 Err(problems) => {
                     errored = true;
                     for problem in problems {
-                        println!("{}", problem)
+                        printer.problem(&problem)
                     }
                 }
             },
@@ -70,19 +187,52 @@ fn main() {
     }
 
     if errored {
-        return;
+        return false;
     }
 
     for (expr, node) in nodes {
-        println!();
-        // println!("    {:?}", expr.expr);
-        println!("    {}", expr.expr);
-        match node.eval(&rt).and_then(|val| val.as_string()) {
-            Ok(s) => println!("{}", s),
-            Err(e) => {
-                println!("\n{}", e);
-                break;
-            }
+        printer.expr(&expr.expr);
+        let result = node.eval(&rt).and_then(|val| printer.value(&val));
+        if let Err(e) = result {
+            printer.error(&e);
+            return false;
         }
     }
+    true
+}
+
+#[test]
+fn echo_and_quiet() {
+    let dir = eval::test_dir();
+    let run_code = |code: &str, echo| {
+        let path = dir.join("main.sdr");
+        std::fs::write(&path, code).unwrap();
+        let mut printer = Printer {
+            echo,
+            out: Vec::new(),
+            err: Vec::new(),
+        };
+        let ok = run(path, Vec::new(), &mut printer);
+        let text = |bytes| String::from_utf8(bytes).unwrap();
+        (ok, text(printer.out), text(printer.err))
+    };
+    let code = "x ← 1 ᛭ 2\n⟨x, 4⟩";
+    assert_eq!(
+        run_code(code, true),
+        (
+            true,
+            "\n    x ← 1 ᛭ 2\n3\n\n    ⟨x, 4⟩\n⟨3 4⟩\n".into(),
+            String::new()
+        )
+    );
+    assert_eq!(run_code(code, false), (true, String::new(), String::new()));
+    let code = "1\n1 ÷ 'a'";
+    let error = "Error: number ÷ character is invalid\n";
+    let (ok, out, err) = run_code(code, true);
+    assert!(!ok && err.is_empty());
+    assert!(out.starts_with(&format!("\n    1\n1\n\n    1 ÷ 'a'\n\n{error}")));
+    let (ok, out, err) = run_code(code, false);
+    assert!(!ok && out.is_empty());
+    assert!(err.starts_with(error));
+    std::fs::remove_dir_all(dir).unwrap();
 }
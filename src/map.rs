@@ -0,0 +1,188 @@
+//! Maps from values to values
+//!
+//! Keys are ordered by [`Val`]'s `Ord` impl. A map is viewed as an array of
+//! `⟨key, value⟩` pairs wherever an array is expected, such as by Each.
+
+use std::{collections::BTreeMap, fmt};
+
+use crate::{
+    array::Array,
+    error::RuntimeResult,
+    eval::rt_error,
+    format::{Format, Formatter},
+    lex::Span,
+    value::{Atom, Val},
+};
+
+#[derive(Clone, Default, PartialEq, Eq, PartialOrd, Ord)]
+pub struct Map {
+    entries: BTreeMap<Val, Val>,
+}
+
+impl Map {
+    /// Create a map from an array of keys and an array of values
+    pub fn new(keys: Val, values: Val, span: &Span) -> RuntimeResult<Self> {
+        let keys = bounded_items(keys, "keys", span)?;
+        let values = bounded_items(values, "values", span)?;
+        if keys.len() != values.len() {
+            return rt_error(
+                format!(
+                    "Cannot make a map from {} keys and {} values",
+                    keys.len(),
+                    values.len()
+                ),
+                span,
+            );
+        }
+        let mut map = Map::default();
+        for (key, value) in keys.into_iter().zip(values) {
+            map.insert(key, value, span)?;
+        }
+        Ok(map)
+    }
+    /// Create a map from an array of `⟨key, value⟩` pairs
+    pub fn from_pairs(pairs: Val, span: &Span) -> RuntimeResult<Self> {
+        let mut map = Map::default();
+        for pair in bounded_items(pairs, "pairs", span)? {
+            let (key, value) = pair_of(pair, span)?;
+            map.insert(key, value, span)?;
+        }
+        Ok(map)
+    }
+    pub fn get(&self, key: &Val) -> Option<&Val> {
+        self.entries.get(key)
+    }
+    pub fn insert(&mut self, key: Val, value: Val, span: &Span) -> RuntimeResult<()> {
+        if let Val::Array(arr) = &key {
+            if arr.len().is_none() {
+                return rt_error("Map keys must be finite", span);
+            }
+        }
+        self.entries.insert(key, value);
+        Ok(())
+    }
+    pub fn iter(&self) -> impl Iterator<Item = (&Val, &Val)> {
+        self.entries.iter()
+    }
+    pub fn keys(&self) -> Array {
+        Array::concrete(self.entries.keys().cloned())
+    }
+    pub fn values(&self) -> Array {
+        Array::concrete(self.entries.values().cloned())
+    }
+    /// The array of `⟨key, value⟩` pairs
+    pub fn pairs(&self) -> Array {
+        Array::concrete(
+            self.entries
+                .iter()
+                .map(|(key, value)| Array::concrete([key.clone(), value.clone()])),
+        )
+    }
+    pub fn matches(&self, other: &Self) -> RuntimeResult<bool> {
+        if self.entries.len() != other.entries.len() {
+            return Ok(false);
+        }
+        for ((a_key, a), (b_key, b)) in self.entries.iter().zip(&other.entries) {
+            if !a_key.matches(b_key)? || !a.matches(b)? {
+                return Ok(false);
+            }
+        }
+        Ok(true)
+    }
+}
+
+fn bounded_items(val: Val, name: &str, span: &Span) -> RuntimeResult<Vec<Val>> {
+    match val {
+        Val::Array(arr) if arr.len().is_some() => arr.into_vec(),
+        Val::Array(_) => rt_error(format!("Map {} must be finite", name), span),
+        val => rt_error(
+            format!(
+                "Map {} must be an array, but it is {}",
+                name,
+                val.type_name()
+            ),
+            span,
+        ),
+    }
+}
+
+/// Split a `⟨key, value⟩` pair
+pub fn pair_of(pair: Val, span: &Span) -> RuntimeResult<(Val, Val)> {
+    match pair {
+        Val::Array(arr) if arr.len() == Some(2) => {
+            let mut items = arr.into_vec()?.into_iter();
+            Ok((items.next().unwrap(), items.next().unwrap()))
+        }
+        val => rt_error(
+            format!("Expected a ⟨key, value⟩ pair, but got {}", val.type_name()),
+            span,
+        ),
+    }
+}
+
+impl fmt::Debug for Map {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "({:?} •Map {:?})", self.keys(), self.values())
+    }
+}
+
+impl Format for Map {
+    fn format(&self, f: &mut Formatter) -> RuntimeResult<()> {
+        fn list<'a>(f: &mut Formatter, vals: impl Iterator<Item = &'a Val>) -> RuntimeResult<()> {
+            f.display('⟨');
+            for (i, val) in vals.enumerate() {
+                if i > 0 {
+                    f.display(' ');
+                }
+                val.format(f)?;
+            }
+            f.display('⟩');
+            Ok(())
+        }
+        f.display('(');
+        list(f, self.entries.keys())?;
+        f.display(" •Map ");
+        list(f, self.entries.values())?;
+        f.display(')');
+        Ok(())
+    }
+}
+
+/// View a map as its array of pairs, leaving other values unchanged
+pub fn pairs_view(val: Val) -> Val {
+    match val {
+        Val::Atom(Atom::Map(map)) => map.pairs().into(),
+        val => val,
+    }
+}
+
+#[test]
+fn maps() {
+    use crate::eval::eval_test;
+    let m = "m ← ⟨\"b\", \"a\"⟩ •Map ⟨2, 1⟩\n";
+    assert_eq!(eval_test(&format!("{}m •Get \"b\"", m)).unwrap(), "2");
+    assert_eq!(
+        eval_test(&format!("{}m ↩ m •Insert ⟨\"c\", 3⟩\n•Values m", m)).unwrap(),
+        "⟨1 2 3⟩"
+    );
+    assert_eq!(
+        eval_test(&format!("{}•Keys m", m)).unwrap(),
+        "\"a\"\n\"b\"\n"
+    );
+    assert_eq!(
+        eval_test("•Map ⟨⟨1, 10⟩, ⟨2, 20⟩⟩").unwrap(),
+        "(⟨1 2⟩ •Map ⟨10 20⟩)"
+    );
+    assert_eq!(
+        eval_test("ᛒ⦑ᛈ ᚭ⦒ •Map ⟨⟨1, 10⟩, ⟨2, 20⟩⟩").unwrap(),
+        "⟨1 2⟩"
+    );
+    assert_eq!(
+        eval_test("(⟨1⟩ •Map ⟨2⟩) •Get 5").unwrap_err(),
+        "Map has no such key"
+    );
+    assert_eq!(
+        eval_test("⟨1, 2⟩ •Map ⟨3⟩").unwrap_err(),
+        "Cannot make a map from 2 keys and 1 values"
+    );
+}
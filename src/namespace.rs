@@ -0,0 +1,152 @@
+//! Namespaces, records of named values
+//!
+//! `⦃ a ← 1, b ← 2 ⦄` evaluates its assignments in a new scope and
+//! collects the bindings into a [`Namespace`]. Fields are accessed with `ns.a`.
+
+use std::{collections::BTreeMap, fmt, rc::Rc};
+
+use crate::{
+    cwt::{FieldValNode, NamespaceValNode, Slot},
+    error::RuntimeResult,
+    eval::{rt_error, Eval},
+    format::{Format, Formatter},
+    lex::{Ident, Span},
+    runtime::Runtime,
+    value::{Atom, Val},
+};
+
+#[derive(Clone, Default, PartialEq, Eq, PartialOrd, Ord)]
+pub struct Namespace {
+    fields: BTreeMap<Ident, Val>,
+}
+
+impl Namespace {
+    pub fn get(&self, name: &Ident) -> Option<&Val> {
+        self.fields.get(name)
+    }
+    pub fn fields(&self) -> impl Iterator<Item = (&Ident, &Val)> {
+        self.fields.iter()
+    }
+    pub fn matches(&self, other: &Self) -> RuntimeResult<bool> {
+        if self.fields.len() != other.fields.len() {
+            return Ok(false);
+        }
+        for ((a_name, a), (b_name, b)) in self.fields.iter().zip(&other.fields) {
+            if a_name != b_name || !a.matches(b)? {
+                return Ok(false);
+            }
+        }
+        Ok(true)
+    }
+}
+
+impl FromIterator<(Ident, Val)> for Namespace {
+    fn from_iter<T>(iter: T) -> Self
+    where
+        T: IntoIterator<Item = (Ident, Val)>,
+    {
+        Namespace {
+            fields: iter.into_iter().collect(),
+        }
+    }
+}
+
+impl fmt::Debug for Namespace {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "⦃")?;
+        for (i, (name, val)) in self.fields.iter().enumerate() {
+            if i > 0 {
+                write!(f, ", ")?;
+            }
+            write!(f, "{} ← {:?}", name, val)?;
+        }
+        write!(f, "⦄")
+    }
+}
+
+impl Format for Namespace {
+    fn format(&self, f: &mut Formatter) -> RuntimeResult<()> {
+        f.display('⦃');
+        for (i, (name, val)) in self.fields.iter().enumerate() {
+            f.display(if i == 0 { " " } else { ", " });
+            f.display(name);
+            f.display(" ← ");
+            val.format(f)?;
+        }
+        if !self.fields.is_empty() {
+            f.display(' ');
+        }
+        f.display('⦄');
+        Ok(())
+    }
+}
+
+impl Namespace {
+    /// Collect the named bindings of a runtime's current scope
+    pub fn from_scope(rt: &Runtime, fields: &[(Ident, usize)], span: &Span) -> RuntimeResult<Self> {
+        fields
+            .iter()
+            .map(|(name, index)| {
+                let slot = Slot {
+                    depth: 0,
+                    index: *index,
+                };
+                match rt.get(slot) {
+                    Some(val) => Ok((name.clone(), val)),
+                    None => rt_error(format!("`{}` has not been bound yet", name), span),
+                }
+            })
+            .collect()
+    }
+}
+
+impl Eval for NamespaceValNode {
+    fn eval(&self, rt: &Runtime) -> RuntimeResult {
+        let rt = rt.push_namespace();
+        rt.eval_nodes(self.nodes.clone())?;
+        Ok(Namespace::from_scope(&rt, &self.fields, &self.span)?.into())
+    }
+}
+
+impl Eval for FieldValNode {
+    fn eval(&self, rt: &Runtime) -> RuntimeResult {
+        match self.ns.eval(rt)? {
+            Val::Atom(Atom::Namespace(ns)) => match ns.get(&self.name) {
+                Some(val) => Ok(val.clone()),
+                None => rt_error(
+                    format!("Namespace has no field `{}`", self.name),
+                    &self.span,
+                ),
+            },
+            val => rt_error(
+                format!("Cannot access field `{}` of {}", self.name, val.type_name()),
+                &self.span,
+            ),
+        }
+    }
+}
+
+impl From<Namespace> for Atom {
+    fn from(ns: Namespace) -> Self {
+        Atom::Namespace(Rc::new(ns))
+    }
+}
+
+#[test]
+fn namespaces() {
+    use crate::eval::eval_test;
+    assert_eq!(
+        eval_test("ns ← ⦃ a ← 1, b ← a ᛭ 1 ⦄\n⟨ns.a, ns.b⟩").unwrap(),
+        "⟨1 2⟩"
+    );
+    assert_eq!(
+        eval_test("⦃ a ← 1, b ← 2 ⦄ ≡ ⦃ b ← 2, a ← 1 ⦄").unwrap(),
+        "1"
+    );
+    assert_eq!(eval_test("⦃ a ← 1 ⦄ ≡ ⦃ a ← 2 ⦄").unwrap(), "0");
+    assert_eq!(
+        eval_test("ns ← ⦃ a ← 1 ⦄\nns.c").unwrap_err(),
+        "Namespace has no field `c`"
+    );
+    assert_eq!(eval_test("⦃ a ← 1, b ← 2 ⦄").unwrap(), "⦃ a ← 1, b ← 2 ⦄");
+}
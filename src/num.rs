@@ -93,6 +93,42 @@ impl Num {
     pub fn log(self, base: Num) -> Self {
         f64::from(self).log(base.into()).into()
     }
+    /// Get the square root
+    pub fn sqrt(self) -> Self {
+        self.float_fn(f64::sqrt)
+    }
+    /// Get the nth root
+    pub fn root(self, n: Num) -> Self {
+        self.pow(Num::Int(1) / n)
+    }
+    /// Get the length of the hypotenuse of a right triangle with this and another number as its legs
+    pub fn hypot(self, other: Num) -> Self {
+        f64::from(self).hypot(other.into()).into()
+    }
+    /// Get the four-quadrant arctangent of this number as the y-coordinate and another as the x-coordinate
+    pub fn atan2(self, x: Num) -> Self {
+        f64::from(self).atan2(x.into()).into()
+    }
+    /// Round to some number of digits after the decimal point
+    ///
+    /// Rounding to a non-positive number of digits produces an integer
+    pub fn round_to(self, digits: Num) -> Self {
+        let digits = i64::from(digits);
+        if digits <= 0 {
+            let factor = Num::Int(10).pow(Num::Int(-digits));
+            (self / factor).round() * factor
+        } else {
+            let factor = 10f64.powi(digits as i32);
+            Num::Float((f64::from(self) * factor).round() / factor)
+        }
+    }
+    /// Apply a floating point function to the number
+    pub fn float_fn<F>(self, f: F) -> Self
+    where
+        F: FnOnce(f64) -> f64,
+    {
+        Num::Float(f(self.into()))
+    }
     /// Get the true modulus of the number with some radix
     pub fn modulus(self, radix: Num) -> Self {
         self.binary_op(radix, modulus, modulus)
@@ -124,38 +160,48 @@ impl Num {
             (Num::Float(a), Num::Float(b)) => float(a, b),
         }
     }
+    /// Format the number the way it was written in the source
+    ///
+    /// Numbers written in scientific notation, with a radix prefix, or as a constant
+    /// glyph keep their form. All others are formatted with `_` digit separators.
     pub fn string_format(&self, string: &str) -> String {
-        if string.contains('e') || string.contains('E') {
-            string.replace('-', "‾")
-        } else {
-            let mut s = String::new();
-            let n = *self;
-            if n < Num::Int(0) {
-                s.push('‾');
+        let mut s = String::new();
+        let n = *self;
+        if n < Num::Int(0) {
+            s.push('‾');
+        }
+        let string = string.trim_start_matches('‾');
+        if string.contains(['e', 'E'])
+            || string.starts_with(|c: char| !c.is_ascii_digit())
+            || ["0x", "0b", "0o"]
+                .iter()
+                .any(|prefix| string.starts_with(prefix))
+        {
+            s.push_str(&string.replace('-', "‾"));
+            return s;
+        }
+        let n_string = n.abs().to_string();
+        let mut parts = n_string.split('.');
+        let left = parts.next().unwrap();
+        let right = parts.next();
+        let underscores = n.abs() >= 10000;
+        for (i, c) in left.chars().enumerate() {
+            let i = left.len() - i - 1;
+            s.push(c);
+            if underscores && i > 0 && i.is_multiple_of(3) {
+                s.push('_');
             }
-            let n_string = n.abs().to_string();
-            let mut parts = n_string.split('.');
-            let left = parts.next().unwrap();
-            let right = parts.next();
-            let underscores = n.abs() >= 10000;
-            for (i, c) in left.chars().enumerate() {
-                let i = left.len() - i - 1;
+        }
+        if let Some(right) = right {
+            s.push('.');
+            for (i, c) in right.chars().enumerate() {
                 s.push(c);
-                if underscores && i > 0 && i % 3 == 0 {
+                if i % 3 == 2 && i + 1 < right.len() {
                     s.push('_');
                 }
             }
-            if let Some(right) = right {
-                s.push('.');
-                for (i, c) in right.chars().enumerate() {
-                    s.push(c);
-                    if i > 0 && i % 3 == 2 {
-                        s.push('_');
-                    }
-                }
-            }
-            s
         }
+        s
     }
 }
 
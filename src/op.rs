@@ -1,11 +1,25 @@
 use std::fmt;
 
+/// The prefix for named operators that have no glyph of their own
+pub const SYSTEM_PREFIX: char = '•';
+
+/// Canonicalize a name the same way identifiers are compared
+fn canon_name(name: &str) -> impl Iterator<Item = char> + '_ {
+    name.chars()
+        .flat_map(char::to_lowercase)
+        .filter(|&c| c != '_')
+}
+
 macro_rules! op {
-    ($name:ident, $($(#[$meta:meta])* ($variant:ident, $glyph:literal $(,$escape:literal)?)),* $(,$no_glyph:ident)* $(,)?) => {
+    (
+        $name:ident
+        $(, $(#[$meta:meta])* ($variant:ident, $glyph:literal $(,$escape:literal)?))* $(,)?
+        $(; $($(#[$no_glyph_meta:meta])* $no_glyph:ident),* $(,)?)?
+    ) => {
         #[derive(Clone, Copy, PartialEq, Eq, PartialOrd, Ord)]
         pub enum $name {
             $($(#[$meta])* $variant,)*
-            $($no_glyph,)*
+            $($($(#[$no_glyph_meta])* $no_glyph,)*)?
         }
 
         impl $name {
@@ -21,6 +35,13 @@ macro_rules! op {
                     _ => None,
                 }
             }
+            #[allow(unused_variables)]
+            pub fn from_name(name: &str) -> Option<Self> {
+                $($(if canon_name(name).eq(canon_name(stringify!($no_glyph))) {
+                    return Some($name::$no_glyph);
+                })*)?
+                None
+            }
         }
 
         impl fmt::Debug for $name {
@@ -33,7 +54,7 @@ macro_rules! op {
             fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
                 match self {
                     $($name::$variant => $glyph.fmt(f),)*
-                    $($name::$no_glyph => stringify!($no_glyph).fmt(f),)*
+                    $($($name::$no_glyph => write!(f, "{}{}", SYSTEM_PREFIX, stringify!($no_glyph)),)*)?
                 }
             }
         }
@@ -47,6 +68,7 @@ pub enum Op {
     Pervasive(Pervasive),
     Rune(RuneOp),
     Other(OtherOp),
+    System(SystemOp),
 }
 
 #[derive(Clone, Copy, PartialEq, Eq, PartialOrd, Ord)]
@@ -64,8 +86,27 @@ op!(
     (Max, '⎡', '^'),
     (Min, '⎣', '_'),
     (Mod, 'ᛁ'),
-    (Pow, '*'),
+    (Pow, '*');
+    /// Natural log/Logarithm
     Log,
+    /// Square root/Root
+    Sqrt,
+    /// Sine
+    Sin,
+    /// Cosine
+    Cos,
+    /// Tangent
+    Tan,
+    /// Arcsine
+    Asin,
+    /// Arccosine
+    Acos,
+    /// Arctangent/Two-argument arctangent
+    Atan,
+    /// ?/Hypotenuse
+    Hypot,
+    /// Round/Round to digits
+    Round,
 );
 
 op!(
@@ -110,6 +151,102 @@ op!(
 
 op!(OtherOp, (Match, '≡', ':'), (DoNotMatch, '≢', ';'));
 
+op!(
+    SystemOp;
+    /// Random number/Random numbers
+    Rand,
+    /// Set random seed
+    Seed,
+    /// Map from pairs/Map from keys and values
+    Map,
+    /// ?/Map lookup
+    Get,
+    /// ?/Map insert
+    Insert,
+    /// Map keys
+    Keys,
+    /// Map values
+    Values,
+    /// Import a file
+    Import,
+    /// Read a file
+    ReadFile,
+    /// Read the lines of a file
+    ReadLines,
+    /// Read the bytes of a file
+    ReadBytes,
+    /// ?/Write a file
+    WriteFile,
+    /// ?/Append to a file
+    AppendFile,
+    /// List a directory
+    ListDir,
+    /// Check if a path exists
+    Exists,
+    /// Command-line arguments
+    Args,
+    /// Read standard input
+    Stdin,
+    /// Lines of standard input
+    StdinLines,
+    /// Print a line
+    Print,
+    /// Write to stdout
+    Write,
+    /// Parse CSV/Parse delimited values
+    Csv,
+    /// Write CSV/Write delimited values
+    ToCsv,
+    /// Decode JSON
+    Json,
+    /// Encode JSON
+    ToJson,
+    /// ?/Split on a delimiter
+    Split,
+    /// ?/Join with a separator
+    JoinWith,
+    /// Trim whitespace
+    Trim,
+    /// ?/Find occurrences
+    Find,
+    /// ?/Replace occurrences
+    Replace,
+    /// Uppercase
+    Upper,
+    /// Lowercase
+    Lower,
+    /// Parse a number
+    ParseNum,
+    /// Format a number/Format a number with digits
+    FormatNum,
+    /// ?/Regex match
+    ReMatch,
+    /// ?/Regex find all
+    ReFind,
+    /// ?/Regex capture groups
+    ReGroups,
+    /// ?/Regex substitute
+    ReSub,
+    /// Format a value/?
+    Format,
+    /// Unformat text/?
+    Unformat,
+    /// ?/Format a template
+    Sprintf,
+    /// Current time/?
+    Now,
+    /// Freeze the clock/?
+    FreezeNow,
+    /// Parse a time/?
+    ParseTime,
+    /// Format a time/Format a time with a pattern
+    FormatTime,
+    /// Time components/?
+    Date,
+    /// Days to seconds/?
+    Days,
+);
+
 impl<P> From<P> for Op
 where
     P: Into<Pervasive>,
@@ -131,6 +268,12 @@ impl From<OtherOp> for Op {
     }
 }
 
+impl From<SystemOp> for Op {
+    fn from(s: SystemOp) -> Self {
+        Op::System(s)
+    }
+}
+
 impl From<MathOp> for Pervasive {
     fn from(m: MathOp) -> Self {
         Pervasive::Math(m)
@@ -166,6 +309,17 @@ impl Op {
             None
         }
     }
+    pub fn from_name(name: &str) -> Option<Self> {
+        if let Some(p) = Pervasive::from_name(name) {
+            Some(Op::Pervasive(p))
+        } else if let Some(r) = RuneOp::from_name(name) {
+            Some(Op::Rune(r))
+        } else if let Some(o) = OtherOp::from_name(name) {
+            Some(Op::Other(o))
+        } else {
+            SystemOp::from_name(name).map(Op::System)
+        }
+    }
 }
 
 impl Pervasive {
@@ -187,6 +341,13 @@ impl Pervasive {
             None
         }
     }
+    pub fn from_name(name: &str) -> Option<Self> {
+        if let Some(m) = MathOp::from_name(name) {
+            Some(Pervasive::Math(m))
+        } else {
+            ComparisonOp::from_name(name).map(Pervasive::Comparison)
+        }
+    }
 }
 
 impl fmt::Debug for Op {
@@ -195,6 +356,7 @@ impl fmt::Debug for Op {
             Op::Pervasive(p) => p.fmt(f),
             Op::Rune(r) => r.fmt(f),
             Op::Other(o) => o.fmt(f),
+            Op::System(s) => s.fmt(f),
         }
     }
 }
@@ -205,6 +367,7 @@ impl fmt::Display for Op {
             Op::Pervasive(p) => p.fmt(f),
             Op::Rune(r) => r.fmt(f),
             Op::Other(o) => o.fmt(f),
+            Op::System(s) => s.fmt(f),
         }
     }
 }
@@ -241,6 +404,8 @@ op!(
     (Ingwaz, 'ᛜ', 'N'),
     /// Flip
     (Othala, 'ᛟ', 'o'),
+    /// Undo
+    (Ear, 'ᛠ', 'U'),
 );
 
 op!(
@@ -249,10 +414,12 @@ op!(
     (Haglaz, 'ᚻ', 'h'),
     /// Beside
     (Ehwaz, 'ᛖ', 'e'),
-    /// ?
+    /// Under
     (Mannaz, 'ᛗ', 'm'),
     /// Choose
     (Dagaz, 'ᛞ', 'd'),
     /// Catch
     (Stan, 'ᛥ', 'S'),
+    /// Repeat
+    (Ger, 'ᛄ', 'R'),
 );
@@ -4,7 +4,6 @@ use crate::{
     ast::*,
     error::{CompileError, CompileResult, IoError},
     lex::*,
-    num::Num,
     op::*,
 };
 
@@ -160,12 +159,36 @@ impl Parser {
     fn assign(&mut self, name: Ident, op: AssignOp, span: Span) -> CompileResult<AssignExpr> {
         let body = self.expect_with("expression", Self::top_expr)?;
         Ok(AssignExpr {
-            name,
+            pattern: Pattern::Ident(name),
             op,
-            body,
+            function: None,
+            body: Some(body),
             span,
         })
     }
+    /// Match the function of a modified assignment, which must be followed by `↩`
+    fn modified_assign_function(&mut self) -> CompileResult<Option<Expr>> {
+        let start = self.curr;
+        let function = if let Some(op) = self.match_to(op) {
+            Expr::Op(op)
+        } else if let Some(ident) = self.match_to(ident) {
+            Expr::Ident(ident)
+        } else if let Some(param) = self.match_to(param) {
+            Expr::Param(param)
+        } else if let Some(expr) = self.parened()? {
+            expr
+        } else {
+            return Ok(None);
+        };
+        if function.role() == Role::Function
+            && self.match_token(TT::Assign(AssignOp::Reassign)).is_some()
+        {
+            Ok(Some(function))
+        } else {
+            self.curr = start;
+            Ok(None)
+        }
+    }
     fn expr(&mut self) -> CompileResult<Option<Expr>> {
         Ok(Some(if let Some(expr) = self.function_or_value_expr()? {
             expr
@@ -288,6 +311,10 @@ impl Parser {
             Expr::Char(char)
         } else if let Some(string) = self.match_to(string) {
             Expr::String(string)
+        } else if let Some(expr) = self.destructure()? {
+            expr
+        } else if let Some(expr) = self.namespace()? {
+            expr
         } else if let Some(expr) = self.array()? {
             expr
         } else {
@@ -305,8 +332,21 @@ impl Parser {
                 let body = self.expect_with("expression", Self::expr)?;
                 Expr::Assign(
                     AssignExpr {
-                        name: ident.data,
+                        pattern: Pattern::Ident(ident.data),
                         op: op.data,
+                        function: None,
+                        body: Some(body),
+                        span: ident.span,
+                    }
+                    .into(),
+                )
+            } else if let Some(function) = self.modified_assign_function()? {
+                let body = self.expr()?;
+                Expr::Assign(
+                    AssignExpr {
+                        pattern: Pattern::Ident(ident.data),
+                        op: AssignOp::Reassign,
+                        function: Some(function),
                         body,
                         span: ident.span,
                     }
@@ -320,6 +360,7 @@ impl Parser {
         } else {
             return Ok(None);
         };
+        let expr = self.fields(expr)?;
         Ok(if expr.role() == role {
             Some(expr)
         } else {
@@ -341,11 +382,15 @@ impl Parser {
         } else {
             return Ok(None);
         };
+        self.newline();
         let mut items = Vec::new();
         while let Some(item) = self.item()? {
             items.push(item);
             self.match_token(TT::Comma);
         }
+        while let Some(Item::Newline) = items.last() {
+            items.pop();
+        }
         let close = self.expect_token(TT::CloseAngleDot)?;
         let span = open.span.join(&close.span);
         if !items.iter().any(|item| matches!(item, Item::Expr(_))) {
@@ -353,6 +398,81 @@ impl Parser {
         }
         Ok(Some(Expr::Function(FunctionLiteral { items, span }.into())))
     }
+    fn namespace(&mut self) -> CompileResult<Option<Expr>> {
+        let open = if let Some(token) = self.match_token(TT::OpenCurly) {
+            token
+        } else {
+            return Ok(None);
+        };
+        self.newline();
+        let mut items = Vec::new();
+        while let Some(item) = self.item()? {
+            items.push(item);
+            self.match_token(TT::Comma);
+        }
+        while let Some(Item::Newline) = items.last() {
+            items.pop();
+        }
+        let close = self.expect_token(TT::CloseCurly)?;
+        let span = open.span.join(&close.span);
+        let expr = Expr::Namespace(NamespaceLiteral { items, span }.into());
+        self.fields(expr).map(Some)
+    }
+    /// Match any field accesses following an expression
+    fn fields(&mut self, mut expr: Expr) -> CompileResult<Expr> {
+        if let Expr::Assign(_) = expr {
+            return Ok(expr);
+        }
+        while self.match_token(TT::Dot).is_some() {
+            let name = self.expect_with("field name", |p| Ok(p.match_to(ident)))?;
+            expr = Expr::Field(FieldExpr { ns: expr, name }.into());
+        }
+        Ok(expr)
+    }
+    /// Match an assignment to an array pattern
+    fn destructure(&mut self) -> CompileResult<Option<Expr>> {
+        let start = self.curr;
+        let (pattern, op) = match self.array_pattern() {
+            Some(pattern) => match self.match_to(assign_op) {
+                Some(op) => (pattern, op),
+                None => {
+                    self.curr = start;
+                    return Ok(None);
+                }
+            },
+            None => {
+                self.curr = start;
+                return Ok(None);
+            }
+        };
+        let body = self.expect_with("expression", Self::expr)?;
+        Ok(Some(Expr::Assign(
+            AssignExpr {
+                pattern: pattern.data,
+                op: op.data,
+                function: None,
+                body: Some(body),
+                span: pattern.span,
+            }
+            .into(),
+        )))
+    }
+    fn array_pattern(&mut self) -> Option<Sp<Pattern>> {
+        let open = self.match_token(TT::OpenAngle)?;
+        let mut patterns = Vec::new();
+        loop {
+            if let Some(ident) = self.match_to(ident) {
+                patterns.push(Pattern::Ident(ident.data));
+            } else if let Some(pattern) = self.array_pattern() {
+                patterns.push(pattern.data);
+            } else {
+                break;
+            }
+            self.match_token(TT::Comma);
+        }
+        let close = self.match_token(TT::CloseAngle)?;
+        Some(open.span.join(&close.span).sp(Pattern::Array(patterns)))
+    }
     fn array(&mut self) -> CompileResult<Option<Expr>> {
         let open = if let Some(token) = self.match_token(TT::OpenAngle) {
             token
@@ -381,9 +501,12 @@ impl Parser {
     }
 }
 
-fn num(tt: &TT) -> Option<Num> {
-    if let TT::Num(num, _) = tt {
-        Some(*num)
+fn num(tt: &TT) -> Option<NumLiteral> {
+    if let TT::Num(num, string) = tt {
+        Some(NumLiteral {
+            num: *num,
+            string: string.clone(),
+        })
     } else {
         None
     }
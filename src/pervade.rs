@@ -167,12 +167,21 @@ pub fn un_pervade_atom(per: Pervasive, x: Atom, span: &Span) -> RuntimeResult {
         (Pervasive::Math(MathOp::Div), Atom::Num(n)) => Ok((Num::Int(1) / n).into()),
         (Pervasive::Math(MathOp::Pow), Atom::Num(n)) => Ok(Num::E.pow(n).into()),
         (Pervasive::Math(MathOp::Log), Atom::Num(n)) => Ok(n.log(Num::E).into()),
+        (Pervasive::Math(MathOp::Sqrt), Atom::Num(n)) => Ok(n.sqrt().into()),
+        (Pervasive::Math(MathOp::Sin), Atom::Num(n)) => Ok(n.float_fn(f64::sin).into()),
+        (Pervasive::Math(MathOp::Cos), Atom::Num(n)) => Ok(n.float_fn(f64::cos).into()),
+        (Pervasive::Math(MathOp::Tan), Atom::Num(n)) => Ok(n.float_fn(f64::tan).into()),
+        (Pervasive::Math(MathOp::Asin), Atom::Num(n)) => Ok(n.float_fn(f64::asin).into()),
+        (Pervasive::Math(MathOp::Acos), Atom::Num(n)) => Ok(n.float_fn(f64::acos).into()),
+        (Pervasive::Math(MathOp::Atan), Atom::Num(n)) => Ok(n.float_fn(f64::atan).into()),
+        (Pervasive::Math(MathOp::Round), Atom::Num(n)) => Ok(n.round().into()),
         (Pervasive::Math(MathOp::Mod), Atom::Num(n)) => Ok(n.abs().into()),
         (Pervasive::Math(MathOp::Max), Atom::Num(n)) => Ok(n.ceil().into()),
         (Pervasive::Math(MathOp::Min), Atom::Num(n)) => Ok(n.floor().into()),
         (Pervasive::Comparison(ComparisonOp::NotEqual), Atom::Num(n)) => {
             Ok((Num::Int(1) - n).into())
         }
+        (Pervasive::Math(MathOp::Hypot), _) => rt_error(format!("{} has no unary form", per), span),
         (per, x) => rt_error(format!("{} {} is invalid", per, x.type_name()), span),
     }
 }
@@ -190,6 +199,13 @@ pub fn bin_pervade_atom(per: Pervasive, w: Atom, x: Atom, span: &Span) -> Runtim
                 MathOp::Min => w.min(x),
                 MathOp::Pow => w.pow(x),
                 MathOp::Log => w.log(x),
+                MathOp::Sqrt => x.root(w),
+                MathOp::Atan => w.atan2(x),
+                MathOp::Hypot => w.hypot(x),
+                MathOp::Round => x.round_to(w),
+                MathOp::Sin | MathOp::Cos | MathOp::Tan | MathOp::Asin | MathOp::Acos => {
+                    return rt_error(format!("{} has no binary form", math), span)
+                }
             })
             .into()),
             (Atom::Char(wc), Atom::Num(xn)) => {
@@ -241,3 +257,34 @@ impl PartialEq for LazyPervade {
 }
 
 impl Eq for LazyPervade {}
+
+#[test]
+fn math() {
+    use crate::eval::eval_test;
+    let cases = [
+        ("•Log 1", "0"),
+        ("•Log *2", "2"),
+        ("8 •Log 2", "3"),
+        ("•Sqrt 16", "4"),
+        ("3 •Sqrt 27", "3"),
+        ("•Sqrt ‾1", "NaN"),
+        ("⟨•Sin 0, •Cos 0, •Tan 0⟩", "⟨0 1 0⟩"),
+        ("⟨2 × •Asin 1, •Acos 1, 4 × •Atan 1⟩ = π", "⟨1 0 1⟩"),
+        ("0 •Atan ‾1", "3.141592653589793"),
+        ("3 •Hypot 4", "5"),
+        ("⟨•Round 2.5, •Round ‾2.5, •Round 7⟩", "⟨3 ‾3 7⟩"),
+        ("2 •Round π", "3.14"),
+        ("‾1 •Round 1234", "1230"),
+    ];
+    for (code, expected) in cases {
+        assert_eq!(eval_test(code).unwrap(), expected, "{}", code);
+    }
+    assert_eq!(
+        eval_test("•Hypot 3").unwrap_err(),
+        "•Hypot has no unary form"
+    );
+    assert_eq!(
+        eval_test("1 •Sin 2").unwrap_err(),
+        "•Sin has no binary form"
+    );
+}
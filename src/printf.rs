@@ -0,0 +1,194 @@
+//! printf-style formatting of values into text
+//!
+//! A conversion is written `%[flags][width][.precision]kind`. The flags are
+//! `-` to pad on the right, `0` to pad numbers with zeros, and `+` to always
+//! write the sign of a number.
+
+use std::{iter::Peekable, str::Chars};
+
+use crate::{
+    array::Array,
+    error::RuntimeResult,
+    eval::rt_error,
+    format::Format,
+    lex::Span,
+    num::Num,
+    text::text_arg,
+    value::{Atom, Val},
+};
+
+/// Fill in a template with an array of arguments
+///
+/// A string or non-array argument is used as the only argument.
+pub fn sprintf(w: &Val, x: Val, span: &Span) -> RuntimeResult {
+    let template = text_arg(w, span)?;
+    let args = match x {
+        Val::Array(arr) if arr.len().is_none() => {
+            return rt_error("Format arguments must be finite", span)
+        }
+        // An empty string is still a single argument
+        x @ Val::Array(Array::AsciiString(_) | Array::Utf32String(_)) => vec![x],
+        Val::Array(arr) if arr.len() == Some(0) => Vec::new(),
+        x if x.as_text()?.is_some() => vec![x],
+        Val::Array(arr) => arr.into_vec()?,
+        x => vec![x],
+    };
+    let mut args = args.into_iter();
+    let mut chars = template.chars().peekable();
+    let mut s = String::new();
+    while let Some(c) = chars.next() {
+        if c != '%' {
+            s.push(c);
+            continue;
+        }
+        if chars.next_if_eq(&'%').is_some() {
+            s.push('%');
+            continue;
+        }
+        let spec = Spec::parse(&mut chars, span)?;
+        let arg = match args.next() {
+            Some(arg) => arg,
+            None => return rt_error("Not enough arguments for format template", span),
+        };
+        spec.write(&arg, &mut s, span)?;
+    }
+    let extra = args.count();
+    if extra > 0 {
+        return rt_error(
+            format!("{} too many arguments for format template", extra),
+            span,
+        );
+    }
+    Ok(Array::string(s).into())
+}
+
+#[derive(Default)]
+struct Spec {
+    left: bool,
+    zero: bool,
+    plus: bool,
+    width: usize,
+    precision: Option<usize>,
+    kind: char,
+}
+
+impl Spec {
+    fn parse(chars: &mut Peekable<Chars>, span: &Span) -> RuntimeResult<Self> {
+        let mut spec = Spec::default();
+        while let Some(flag) = chars.next_if(|c| "-0+".contains(*c)) {
+            match flag {
+                '-' => spec.left = true,
+                '0' => spec.zero = true,
+                _ => spec.plus = true,
+            }
+        }
+        spec.width = digits(chars).unwrap_or(0);
+        if chars.next_if_eq(&'.').is_some() {
+            spec.precision = Some(digits(chars).unwrap_or(0));
+        }
+        spec.kind = match chars.next() {
+            Some(kind) if "dfexs".contains(kind) => kind,
+            Some(kind) => return rt_error(format!("Unknown format conversion `%{}`", kind), span),
+            None => return rt_error("Format template ends in the middle of a conversion", span),
+        };
+        Ok(spec)
+    }
+    fn write(&self, arg: &Val, s: &mut String, span: &Span) -> RuntimeResult<()> {
+        let (sign, body) = if self.kind == 's' {
+            let mut text = match arg {
+                Val::Atom(Atom::Char(c)) => c.to_string(),
+                arg => match arg.as_text()? {
+                    Some(text) => text,
+                    None => arg.as_string()?,
+                },
+            };
+            if let Some(precision) = self.precision {
+                text = text.chars().take(precision).collect();
+            }
+            ("", text)
+        } else {
+            let num = match arg {
+                Val::Atom(Atom::Num(num)) => *num,
+                arg => {
+                    return rt_error(
+                        format!(
+                            "`%{}` expects a number, but got {}",
+                            self.kind,
+                            arg.type_name()
+                        ),
+                        span,
+                    )
+                }
+            };
+            let f = f64::from(num);
+            let int = || match num {
+                Num::Int(i) => i,
+                Num::Float(f) => f.round() as i64,
+            };
+            let body = match self.kind {
+                'd' => int().unsigned_abs().to_string(),
+                'x' => format!("{:x}", int().unsigned_abs()),
+                'f' => format!("{:.*}", self.precision.unwrap_or(6), f.abs()),
+                _ => format!("{:.*e}", self.precision.unwrap_or(6), f.abs()),
+            };
+            let negative = if "dx".contains(self.kind) {
+                int() < 0
+            } else {
+                f.is_sign_negative() && f != 0.0
+            };
+            let sign = if negative {
+                "-"
+            } else if self.plus {
+                "+"
+            } else {
+                ""
+            };
+            (sign, body)
+        };
+        let len = sign.chars().count() + body.chars().count();
+        let padding = self.width.saturating_sub(len);
+        if self.left {
+            s.push_str(sign);
+            s.push_str(&body);
+            s.extend((0..padding).map(|_| ' '));
+        } else if self.zero && self.kind != 's' {
+            s.push_str(sign);
+            s.extend((0..padding).map(|_| '0'));
+            s.push_str(&body);
+        } else {
+            s.extend((0..padding).map(|_| ' '));
+            s.push_str(sign);
+            s.push_str(&body);
+        }
+        Ok(())
+    }
+}
+
+fn digits(chars: &mut Peekable<Chars>) -> Option<usize> {
+    let mut n = None;
+    while let Some(d) = chars.next_if(char::is_ascii_digit) {
+        n = Some(n.unwrap_or(0) * 10 + d.to_digit(10).unwrap() as usize);
+    }
+    n
+}
+
+#[test]
+fn templates() {
+    use crate::eval::eval_test;
+    let code = concat!(
+        r#""%5d|%-6.2f|%05d|%+d|%s|%.2s|%x|%%|%s" •Sprintf "#,
+        r#"⟨42, 3.14159, ‾7, 3, "hi", "hello", 255, ⟨1, 2⟩⟩"#
+    );
+    assert_eq!(
+        eval_test(code).unwrap(),
+        r#""   42|3.14  |-0007|+3|hi|he|ff|%|⟨1 2⟩""#
+    );
+    assert_eq!(eval_test(r#""%.3e" •Sprintf 1250"#).unwrap(), "\"1.250e3\"");
+    assert_eq!(eval_test(r#""<%s>" •Sprintf "ᚠᚢ""#).unwrap(), "\"<ᚠᚢ>\"");
+    assert_eq!(eval_test(r#""<%s>" •Sprintf """#).unwrap(), "\"<>\"");
+    assert_eq!(eval_test(r#""<>" •Sprintf ⟨⟩"#).unwrap(), "\"<>\"");
+    assert_eq!(
+        eval_test(r#""%d %d" •Sprintf ⟨1⟩"#).unwrap_err(),
+        "Not enough arguments for format template"
+    );
+}
@@ -0,0 +1,96 @@
+//! A small deterministic pseudo-random number generator
+
+use std::time::{SystemTime, UNIX_EPOCH};
+
+const GOLDEN_GAMMA: u64 = 0x9e37_79b9_7f4a_7c15;
+
+/// Scramble the bits of a number with the SplitMix64 finalizer
+pub fn mix(mut z: u64) -> u64 {
+    z = (z ^ (z >> 30)).wrapping_mul(0xbf58_476d_1ce4_e5b9);
+    z = (z ^ (z >> 27)).wrapping_mul(0x94d0_49bb_1331_11eb);
+    z ^ (z >> 31)
+}
+
+/// Get the random bits at some position in the stream of a seed
+///
+/// This allows random-access into a random sequence without generating
+/// every number before it.
+pub fn nth(seed: u64, n: u64) -> u64 {
+    mix(seed.wrapping_add(n.wrapping_add(1).wrapping_mul(GOLDEN_GAMMA)))
+}
+
+/// Convert random bits to a uniform float in [0, 1)
+pub fn unit_float(bits: u64) -> f64 {
+    (bits >> 11) as f64 / (1u64 << 53) as f64
+}
+
+/// A SplitMix64 generator
+#[derive(Debug, Clone)]
+pub struct Rng {
+    state: u64,
+}
+
+impl Default for Rng {
+    fn default() -> Self {
+        let nanos = SystemTime::now()
+            .duration_since(UNIX_EPOCH)
+            .map(|dur| dur.as_nanos() as u64)
+            .unwrap_or_default();
+        Rng::new(nanos)
+    }
+}
+
+impl Rng {
+    pub fn new(seed: u64) -> Self {
+        Rng { state: seed }
+    }
+    pub fn next_u64(&mut self) -> u64 {
+        self.state = self.state.wrapping_add(GOLDEN_GAMMA);
+        mix(self.state)
+    }
+    pub fn next_float(&mut self) -> f64 {
+        unit_float(self.next_u64())
+    }
+}
+
+#[test]
+fn seeded_rng_is_deterministic() {
+    let mut a = Rng::new(42);
+    let mut b = Rng::new(42);
+    for n in 0..100 {
+        let bits = a.next_u64();
+        assert_eq!(bits, b.next_u64());
+        assert_eq!(bits, nth(42, n));
+        assert!((0.0..1.0).contains(&unit_float(bits)));
+    }
+}
+
+#[test]
+fn seeded_rand() {
+    use crate::eval::eval_test;
+    let twice = |seed| format!("•Seed {seed}\na ← 5 •Rand 100\n•Seed 7\na ≡ 5 •Rand 100");
+    assert_eq!(eval_test(&twice(7)).unwrap(), "1");
+    assert_eq!(eval_test(&twice(8)).unwrap(), "0");
+    assert_eq!(
+        eval_test("•Seed 1\nx ← •Rand 0\n•Seed 1\n⟨x = •Rand 0, x < 1⟩").unwrap(),
+        "⟨1 1⟩"
+    );
+    assert_eq!(eval_test("•Seed 1\nᚱ⎡ 100 •Rand 3").unwrap(), "2");
+    let error = |code| eval_test(code).unwrap_err();
+    assert_eq!(
+        error("2.5 •Rand 10"),
+        "Random count must be a natural number, but it is 2.5"
+    );
+    assert_eq!(
+        error("‾1 •Rand 10"),
+        "Random count must be a natural number, but it is ‾1"
+    );
+    assert_eq!(
+        error("•Rand 0.5"),
+        "Random bound must be a natural number, but it is 0.5"
+    );
+    assert_eq!(
+        error("'a' •Rand 3"),
+        "character cannot be used as a random count"
+    );
+}
@@ -0,0 +1,112 @@
+//! Evaluation of regular expression operators
+//!
+//! Each call site keeps the last pattern it compiled, so a pattern used in a
+//! loop is only compiled once.
+
+use std::{collections::HashMap, path::Path, rc::Rc};
+
+use regex::Regex;
+
+use crate::{
+    array::Array, error::RuntimeResult, eval::rt_error, lex::Span, runtime::Runtime,
+    text::text_arg, value::Val,
+};
+
+/// Compiled patterns, keyed by the location of the call that compiled them
+#[derive(Default)]
+pub struct RegexCache {
+    regexes: HashMap<(Rc<Path>, usize), Regex>,
+}
+
+impl Runtime {
+    fn regex(&self, pattern: &Val, span: &Span) -> RuntimeResult<Regex> {
+        let pattern = text_arg(pattern, span)?;
+        let key = (span.file.clone(), span.loc.pos);
+        if let Some(regex) = self.regexes().borrow().regexes.get(&key) {
+            if regex.as_str() == pattern {
+                return Ok(regex.clone());
+            }
+        }
+        let regex = match Regex::new(&pattern) {
+            Ok(regex) => regex,
+            Err(e) => return rt_error(format!("Invalid pattern: {}", e), span),
+        };
+        self.regexes()
+            .borrow_mut()
+            .regexes
+            .insert(key, regex.clone());
+        Ok(regex)
+    }
+    /// Check if a pattern matches anywhere in a string
+    pub fn regex_match(&self, w: &Val, x: &Val, span: &Span) -> RuntimeResult {
+        let regex = self.regex(w, span)?;
+        Ok(regex.is_match(&text_arg(x, span)?).into())
+    }
+    /// Get the `⟨start, match⟩` pair of each match of a pattern
+    pub fn regex_find(&self, w: &Val, x: &Val, span: &Span) -> RuntimeResult {
+        let regex = self.regex(w, span)?;
+        let text = text_arg(x, span)?;
+        Ok(Array::concrete(regex.find_iter(&text).map(|m| {
+            let start = text[..m.start()].chars().count() as i64;
+            Array::concrete([Val::from(start), Array::string(m.as_str()).into()])
+        }))
+        .into())
+    }
+    /// Get the capture groups of each match of a pattern,
+    /// starting with the whole match
+    pub fn regex_groups(&self, w: &Val, x: &Val, span: &Span) -> RuntimeResult {
+        let regex = self.regex(w, span)?;
+        let text = text_arg(x, span)?;
+        Ok(Array::concrete(regex.captures_iter(&text).map(|caps| {
+            Array::concrete(
+                caps.iter()
+                    .map(|group| Array::string(group.map_or("", |m| m.as_str()))),
+            )
+        }))
+        .into())
+    }
+    /// Replace each match of the first item of `w` with the second
+    pub fn regex_sub(&self, w: &Val, x: &Val, span: &Span) -> RuntimeResult {
+        let (pattern, replacement) = match w {
+            Val::Array(arr) if arr.len() == Some(2) && w.as_text()?.is_none() => (
+                arr.get(0)?.expect("pair has a first item").into_owned(),
+                arr.get(1)?.expect("pair has a second item").into_owned(),
+            ),
+            w => {
+                return rt_error(
+                    format!(
+                        "Substitute expects a ⟨pattern, replacement⟩ pair, but got {}",
+                        w.type_name()
+                    ),
+                    span,
+                )
+            }
+        };
+        let regex = self.regex(&pattern, span)?;
+        let replacement = text_arg(&replacement, span)?;
+        let text = text_arg(x, span)?;
+        Ok(Array::string(regex.replace_all(&text, replacement.as_str())).into())
+    }
+}
+
+#[test]
+fn regexes() {
+    use crate::eval::eval_test;
+    assert_eq!(eval_test(r#""\\d+" •ReMatch "ab12""#).unwrap(), "1");
+    assert_eq!(eval_test(r#""^\\d+$" •ReMatch "ab12""#).unwrap(), "0");
+    assert_eq!(
+        eval_test(r#""\\d+" •ReFind "a1 ᚠ23""#).unwrap(),
+        "1\n\"1\"\n\n4\n\"23\"\n\n"
+    );
+    assert_eq!(
+        eval_test(r#""(\\w)=(\\d)?" •ReGroups "a=1 b=""#).unwrap(),
+        "\"a=1\"\n\"a\"\n\"1\"\n\n\"b=\"\n\"b\"\n⟨⟩\n\n"
+    );
+    assert_eq!(
+        eval_test(r#"⟨"(\\w+)@(\\w+)", "$2 at $1"⟩ •ReSub "me@home""#).unwrap(),
+        "\"home at me\""
+    );
+    assert!(eval_test(r#""(" •ReMatch "a""#)
+        .unwrap_err()
+        .starts_with("Invalid pattern"));
+}
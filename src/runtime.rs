@@ -1,69 +1,174 @@
-use std::{
-    cell::{Ref, RefCell, RefMut},
-    cmp::Ordering,
-    collections::HashMap,
-    fmt,
-    mem::swap,
-    rc::Rc,
-};
+use std::{cell::RefCell, cmp::Ordering, fmt, rc::Rc};
 
 use crate::{
-    lex::{Ident, ParamPlace},
-    value::Val,
+    cwt::Slot,
+    function::Function,
+    import::Imports,
+    io::LazyStdin,
+    lex::ParamPlace,
+    random::Rng,
+    re::RegexCache,
+    time::Clock,
+    value::{Atom, Val},
 };
 
 #[derive(Clone)]
 pub struct Runtime {
-    scope: Rc<RefCell<Scope>>,
+    scope: Rc<Scope>,
+    rng: Rc<RefCell<Rng>>,
+    imports: Rc<RefCell<Imports>>,
+    args: Rc<[String]>,
+    stdin: Rc<LazyStdin>,
+    regexes: Rc<RefCell<RegexCache>>,
+    clock: Rc<Clock>,
 }
 
 impl Default for Runtime {
     fn default() -> Self {
         Runtime {
             scope: Default::default(),
+            rng: Default::default(),
+            imports: Default::default(),
+            args: Rc::new([]),
+            stdin: Default::default(),
+            regexes: Default::default(),
+            clock: Default::default(),
         }
     }
 }
 
 impl Runtime {
+    /// Seed the random number generator
+    pub fn seed(&self, seed: u64) {
+        *self.rng.borrow_mut() = Rng::new(seed);
+    }
+    pub fn random(&self) -> u64 {
+        self.rng.borrow_mut().next_u64()
+    }
     pub fn push(&self) -> Self {
-        let rt = self.clone();
-        let mut new = Scope::default();
-        let mut scope = rt.scope.borrow_mut();
-        swap(&mut new, &mut scope);
-        scope.parent = Some(Rc::new(new));
-        drop(scope);
+        Runtime {
+            scope: Scope {
+                parent: Some(self.scope.clone()),
+                ..Default::default()
+            }
+            .into(),
+            rng: self.rng.clone(),
+            imports: self.imports.clone(),
+            args: self.args.clone(),
+            stdin: self.stdin.clone(),
+            regexes: self.regexes.clone(),
+            clock: self.clock.clone(),
+        }
+    }
+    /// Create a runtime with an empty top-level scope
+    /// that shares this one's random number generator and imports
+    pub fn fresh(&self) -> Self {
+        Runtime {
+            scope: Default::default(),
+            rng: self.rng.clone(),
+            imports: self.imports.clone(),
+            args: self.args.clone(),
+            stdin: self.stdin.clone(),
+            regexes: self.regexes.clone(),
+            clock: self.clock.clone(),
+        }
+    }
+    /// Set the command-line arguments given to the script
+    pub fn with_args(self, args: Vec<String>) -> Self {
+        Runtime {
+            args: args.into(),
+            ..self
+        }
+    }
+    /// Read the given input instead of standard input
+    #[cfg(test)]
+    pub fn with_stdin(self, input: impl std::io::BufRead + 'static) -> Self {
+        Runtime {
+            stdin: LazyStdin::new(input).into(),
+            ..self
+        }
+    }
+    pub fn args(&self) -> &[String] {
+        &self.args
+    }
+    pub fn stdin(&self) -> &Rc<LazyStdin> {
+        &self.stdin
+    }
+    pub fn regexes(&self) -> &RefCell<RegexCache> {
+        &self.regexes
+    }
+    pub fn clock(&self) -> &Clock {
+        &self.clock
+    }
+    pub fn imports(&self) -> &RefCell<Imports> {
+        &self.imports
+    }
+    /// Push a scope for the body of a namespace literal,
+    /// which shares the params of the enclosing scope
+    pub fn push_namespace(&self) -> Self {
+        let rt = self.push();
+        *rt.scope.params.borrow_mut() = self.scope.params.borrow().clone();
+        rt
+    }
+    /// Push a scope for a call to a function or modifier literal,
+    /// binding its self-reference params
+    pub fn push_call(&self, this: Function) -> Self {
+        let rt = self.push();
+        match &this {
+            Function::UnMod(un_mod) => {
+                rt.bind_param(ParamPlace::SelfUnMod, Atom::UnMod(un_mod.m.clone()).into())
+            }
+            Function::BinMod(bin_mod) => rt.bind_param(
+                ParamPlace::SelfBinMod,
+                Atom::BinMod(bin_mod.m.clone()).into(),
+            ),
+            _ => {}
+        }
+        rt.bind_param(ParamPlace::SelfFunction, this.into());
         rt
     }
     pub fn bind_param(&self, place: ParamPlace, val: Val) {
-        let mut scope = self.scope.borrow_mut();
+        let mut params = self.scope.params.borrow_mut();
         match place {
-            ParamPlace::X => scope.params.x = Some(val),
-            ParamPlace::W => scope.params.w = Some(val),
-            ParamPlace::F => scope.params.f = Some(val),
-            ParamPlace::G => scope.params.g = Some(val),
+            ParamPlace::X => params.x = Some(val),
+            ParamPlace::W => params.w = Some(val),
+            ParamPlace::F => params.f = Some(val),
+            ParamPlace::G => params.g = Some(val),
+            ParamPlace::SelfFunction => params.s = Some(val),
+            ParamPlace::SelfUnMod | ParamPlace::SelfBinMod => params.m = Some(val),
         }
     }
     pub fn get_param(&self, place: ParamPlace) -> Option<Val> {
-        let scope = self.scope.borrow();
+        let params = self.scope.params.borrow();
         match place {
-            ParamPlace::X => scope.params.x.clone(),
-            ParamPlace::W => scope.params.w.clone(),
-            ParamPlace::F => scope.params.f.clone(),
-            ParamPlace::G => scope.params.g.clone(),
+            ParamPlace::X => params.x.clone(),
+            ParamPlace::W => params.w.clone(),
+            ParamPlace::F => params.f.clone(),
+            ParamPlace::G => params.g.clone(),
+            ParamPlace::SelfFunction => params.s.clone(),
+            ParamPlace::SelfUnMod | ParamPlace::SelfBinMod => params.m.clone(),
         }
     }
-    pub fn bind(&self, name: Ident, val: Val) {
-        self.scope.borrow_mut().bindings.insert(name, val.into());
+    /// Bind a value to a slot in the current scope
+    pub fn bind(&self, index: usize, val: Val) {
+        let mut bindings = self.scope.bindings.borrow_mut();
+        if index >= bindings.len() {
+            bindings.resize(index + 1, None);
+        }
+        bindings[index] = Some(val);
     }
-    pub fn get(&self, name: &Ident) -> Option<Val> {
-        self.scope.borrow().get(name).map(|val| val.clone())
+    pub fn get(&self, slot: Slot) -> Option<Val> {
+        let scope = self.scope.ancestor(slot.depth)?;
+        let bindings = scope.bindings.borrow();
+        bindings.get(slot.index).cloned().flatten()
     }
-    pub fn get_mut<F, R>(&self, name: &Ident, f: F) -> Option<R>
+    pub fn get_mut<F, R>(&self, slot: Slot, f: F) -> Option<R>
     where
         F: FnOnce(&mut Val) -> R,
     {
-        self.scope.borrow().get_mut(name).map(|mut val| f(&mut val))
+        let scope = self.scope.ancestor(slot.depth)?;
+        let mut bindings = scope.bindings.borrow_mut();
+        bindings.get_mut(slot.index)?.as_mut().map(f)
     }
 }
 
@@ -93,38 +198,29 @@ impl Ord for Runtime {
     }
 }
 
-#[derive(Default)]
+#[derive(Default, Clone)]
 pub struct Params {
     x: Option<Val>,
     w: Option<Val>,
     f: Option<Val>,
     g: Option<Val>,
+    s: Option<Val>,
+    m: Option<Val>,
 }
 
 #[derive(Default)]
 struct Scope {
     parent: Option<Rc<Self>>,
-    params: Params,
-    bindings: HashMap<Ident, RefCell<Val>>,
+    params: RefCell<Params>,
+    bindings: RefCell<Vec<Option<Val>>>,
 }
 
 impl Scope {
-    fn get(&self, name: &Ident) -> Option<Ref<Val>> {
-        if let Some(val) = self.bindings.get(name) {
-            Some(val.borrow())
-        } else if let Some(parent) = &self.parent {
-            parent.get(name)
-        } else {
-            None
-        }
-    }
-    fn get_mut(&self, name: &Ident) -> Option<RefMut<Val>> {
-        if let Some(val) = self.bindings.get(name) {
-            Some(val.borrow_mut())
-        } else if let Some(parent) = &self.parent {
-            parent.get_mut(name)
-        } else {
-            None
+    fn ancestor(&self, depth: usize) -> Option<&Self> {
+        let mut scope = self;
+        for _ in 0..depth {
+            scope = scope.parent.as_deref()?;
         }
+        Some(scope)
     }
 }
@@ -0,0 +1,168 @@
+//! Evaluation of system operators
+
+use crate::{
+    array::{random_num, Array, LazyRandom},
+    csv,
+    error::RuntimeResult,
+    eval::rt_error,
+    io, json,
+    lex::Span,
+    map::{pair_of, Map},
+    num::Num,
+    op::SystemOp,
+    printf,
+    runtime::Runtime,
+    text, time,
+    value::{Atom, Val},
+};
+
+impl Runtime {
+    pub fn system_un(&self, sys: SystemOp, x: Val, span: &Span) -> RuntimeResult {
+        match sys {
+            SystemOp::Rand => {
+                let below = random_bound(x, span)?;
+                if below.is_infinite() {
+                    Ok(Array::Random(LazyRandom::new(self.random(), 0i64.into()).into()).into())
+                } else {
+                    Ok(random_num(self.random(), below).into())
+                }
+            }
+            SystemOp::Seed => match x {
+                Val::Atom(Atom::Num(n)) => {
+                    self.seed(i64::from(n) as u64);
+                    Ok(x)
+                }
+                x => rt_error(format!("{} cannot be used as a seed", x.type_name()), span),
+            },
+            SystemOp::Import => self.import(x, span),
+            SystemOp::ReadFile => io::read_file(&x, span),
+            SystemOp::ReadLines => io::read_lines(&x, span),
+            SystemOp::ReadBytes => io::read_bytes(&x, span),
+            SystemOp::ListDir => io::list_dir(&x, span),
+            SystemOp::Exists => io::exists(&x, span),
+            SystemOp::Args => {
+                Ok(Array::concrete(self.args().iter().cloned().map(Array::string)).into())
+            }
+            SystemOp::Csv => csv_parse(None, x, span),
+            SystemOp::ToCsv => csv_write(None, x, span),
+            SystemOp::Json => match x.as_text()? {
+                Some(text) => json::decode(&text, span),
+                None => rt_error(format!("{} cannot be decoded as JSON", x.type_name()), span),
+            },
+            SystemOp::ToJson => Ok(Array::string(json::encode(&x, span)?).into()),
+            SystemOp::Trim => text::trim(&x, span),
+            SystemOp::Upper => text::upper(&x, span),
+            SystemOp::Lower => text::lower(&x, span),
+            SystemOp::ParseNum => text::parse_num(&x, span),
+            SystemOp::FormatNum => text::format_num(None, &x, span),
+            SystemOp::Format => text::format(&x),
+            SystemOp::Unformat => text::unformat(&x, span),
+            SystemOp::Now => self.now(),
+            SystemOp::FreezeNow => self.freeze_now(x, span),
+            SystemOp::ParseTime => time::parse_time(&x, span),
+            SystemOp::FormatTime => time::format_time(None, &x, span),
+            SystemOp::Date => time::date(&x, span),
+            SystemOp::Days => time::days(&x, span),
+            SystemOp::Print => io::print(x, true, span),
+            SystemOp::Write => io::print(x, false, span),
+            SystemOp::Stdin => Ok(Array::string(self.stdin().read_all()?).into()),
+            SystemOp::StdinLines => Ok(Array::Stdin(self.stdin().clone()).into()),
+            SystemOp::Map => Ok(Map::from_pairs(x, span)?.into()),
+            SystemOp::Keys => Ok(map_arg(&x, span)?.keys().into()),
+            SystemOp::Values => Ok(map_arg(&x, span)?.values().into()),
+            sys => rt_error(format!("{} has no unary form", sys), span),
+        }
+    }
+    pub fn system_bin(&self, sys: SystemOp, w: Val, x: Val, span: &Span) -> RuntimeResult {
+        match sys {
+            SystemOp::Rand => {
+                let count = match w {
+                    Val::Atom(Atom::Num(n)) if n >= 0 && n == n.floor() => n,
+                    Val::Atom(Atom::Num(n)) => {
+                        return rt_error(
+                            format!("Random count must be a natural number, but it is {}", n),
+                            span,
+                        )
+                    }
+                    w => {
+                        return rt_error(
+                            format!("{} cannot be used as a random count", w.type_name()),
+                            span,
+                        )
+                    }
+                };
+                let below = random_bound(x, span)?;
+                if below.is_infinite() {
+                    return rt_error("Random numbers must have a finite bound", span);
+                }
+                Ok(if count.is_infinite() {
+                    Array::Random(LazyRandom::new(self.random(), below).into())
+                } else {
+                    Array::concrete((0..i64::from(count)).map(|_| random_num(self.random(), below)))
+                }
+                .into())
+            }
+            SystemOp::Map => Ok(Map::new(w, x, span)?.into()),
+            SystemOp::Csv => csv_parse(Some(w), x, span),
+            SystemOp::ToCsv => csv_write(Some(w), x, span),
+            SystemOp::Split => text::split(&w, &x, span),
+            SystemOp::JoinWith => text::join_with(&w, &x, span),
+            SystemOp::Find => text::find(&w, &x, span),
+            SystemOp::Replace => text::replace(&w, &x, span),
+            SystemOp::FormatNum => text::format_num(Some(&w), &x, span),
+            SystemOp::ReMatch => self.regex_match(&w, &x, span),
+            SystemOp::ReFind => self.regex_find(&w, &x, span),
+            SystemOp::ReGroups => self.regex_groups(&w, &x, span),
+            SystemOp::ReSub => self.regex_sub(&w, &x, span),
+            SystemOp::Sprintf => printf::sprintf(&w, x, span),
+            SystemOp::FormatTime => time::format_time(Some(&w), &x, span),
+            SystemOp::WriteFile => io::write_file(&w, x, false, span),
+            SystemOp::AppendFile => io::write_file(&w, x, true, span),
+            SystemOp::Get => match map_arg(&w, span)?.get(&x) {
+                Some(val) => Ok(val.clone()),
+                None => rt_error("Map has no such key", span),
+            },
+            SystemOp::Insert => {
+                let mut map = map_arg(&w, span)?.clone();
+                let (key, value) = pair_of(x, span)?;
+                map.insert(key, value, span)?;
+                Ok(map.into())
+            }
+            sys => rt_error(format!("{} has no binary form", sys), span),
+        }
+    }
+}
+
+fn random_bound(x: Val, span: &Span) -> RuntimeResult<Num> {
+    match x {
+        Val::Atom(Atom::Num(n)) if n >= 0 && n == n.floor() => Ok(n),
+        Val::Atom(Atom::Num(n)) => rt_error(
+            format!("Random bound must be a natural number, but it is {}", n),
+            span,
+        ),
+        x => rt_error(
+            format!("{} cannot be used as a random bound", x.type_name()),
+            span,
+        ),
+    }
+}
+
+fn map_arg<'a>(val: &'a Val, span: &Span) -> RuntimeResult<&'a Map> {
+    match val {
+        Val::Atom(Atom::Map(map)) => Ok(map),
+        val => rt_error(format!("{} is not a map", val.type_name()), span),
+    }
+}
+
+fn csv_parse(w: Option<Val>, x: Val, span: &Span) -> RuntimeResult {
+    let delim = csv::delimiter(w, span)?;
+    match x.as_text()? {
+        Some(text) => Ok(csv::parse(&text, delim, span)?.into()),
+        None => rt_error(format!("{} cannot be parsed as CSV", x.type_name()), span),
+    }
+}
+
+fn csv_write(w: Option<Val>, x: Val, span: &Span) -> RuntimeResult {
+    let delim = csv::delimiter(w, span)?;
+    Ok(Array::string(csv::write(&x, delim, span)?).into())
+}
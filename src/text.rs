@@ -0,0 +1,248 @@
+//! Evaluation of text operators
+//!
+//! These work on the text of strings directly, so ASCII strings are never
+//! split into separate character values.
+
+use crate::{
+    array::Array,
+    error::RuntimeResult,
+    eval::rt_error,
+    format::Format,
+    lex::{lex_text, Span, TT},
+    num::Num,
+    value::{Atom, Val},
+};
+
+/// Get the text of a string or character
+pub fn text_arg(val: &Val, span: &Span) -> RuntimeResult<String> {
+    match val {
+        Val::Atom(Atom::Char(c)) => Ok(c.to_string()),
+        val => match val.as_text()? {
+            Some(text) => Ok(text),
+            None => rt_error(
+                format!("Expected a string, but got {}", val.type_name()),
+                span,
+            ),
+        },
+    }
+}
+
+fn strings(texts: impl IntoIterator<Item = impl Into<String>>) -> Val {
+    Array::concrete(texts.into_iter().map(|s| Array::string(s.into()))).into()
+}
+
+pub fn split(w: &Val, x: &Val, span: &Span) -> RuntimeResult {
+    let delim = text_arg(w, span)?;
+    if delim.is_empty() {
+        return rt_error("Split delimiter cannot be empty", span);
+    }
+    Ok(strings(text_arg(x, span)?.split(&delim)))
+}
+
+pub fn join_with(w: &Val, x: &Val, span: &Span) -> RuntimeResult {
+    let sep = text_arg(w, span)?;
+    let parts = match x {
+        Val::Array(arr) if arr.len().is_some() => arr
+            .iter()
+            .map(|part| text_arg(&*part?, span))
+            .collect::<RuntimeResult<Vec<String>>>()?,
+        x => {
+            return rt_error(
+                format!("Expected an array of strings, but got {}", x.type_name()),
+                span,
+            )
+        }
+    };
+    Ok(Array::string(parts.join(&sep)).into())
+}
+
+pub fn trim(x: &Val, span: &Span) -> RuntimeResult {
+    Ok(Array::string(text_arg(x, span)?.trim()).into())
+}
+
+/// Get the character indices where `w` occurs in `x`
+pub fn find(w: &Val, x: &Val, span: &Span) -> RuntimeResult {
+    let needle = text_arg(w, span)?;
+    if needle.is_empty() {
+        return rt_error("Cannot find an empty string", span);
+    }
+    let haystack = text_arg(x, span)?;
+    let indices = haystack
+        .match_indices(&needle)
+        .map(|(i, _)| haystack[..i].chars().count() as i64);
+    Ok(Array::concrete(indices).into())
+}
+
+/// Replace each occurrence of the first item of `w` in `x` with the second
+pub fn replace(w: &Val, x: &Val, span: &Span) -> RuntimeResult {
+    let (from, to) = match w {
+        Val::Array(arr) if arr.len() == Some(2) && w.as_text()?.is_none() => {
+            let from = text_arg(&arr.get(0)?.expect("pair has a first item"), span)?;
+            let to = text_arg(&arr.get(1)?.expect("pair has a second item"), span)?;
+            (from, to)
+        }
+        w => {
+            return rt_error(
+                format!(
+                    "Replace expects a ⟨from, to⟩ pair of strings, but got {}",
+                    w.type_name()
+                ),
+                span,
+            )
+        }
+    };
+    if from.is_empty() {
+        return rt_error("Cannot replace an empty string", span);
+    }
+    Ok(Array::string(text_arg(x, span)?.replace(&from, &to)).into())
+}
+
+pub fn upper(x: &Val, span: &Span) -> RuntimeResult {
+    case(x, span, char::to_uppercase)
+}
+
+pub fn lower(x: &Val, span: &Span) -> RuntimeResult {
+    case(x, span, char::to_lowercase)
+}
+
+fn case<I>(x: &Val, span: &Span, f: fn(char) -> I) -> RuntimeResult
+where
+    I: Iterator<Item = char>,
+{
+    match x {
+        Val::Atom(Atom::Char(c)) => Ok(f(*c).next().unwrap_or(*c).into()),
+        x => Ok(Array::string(text_arg(x, span)?.chars().flat_map(f).collect::<String>()).into()),
+    }
+}
+
+/// Parse text as a single number, written either as a literal or in ASCII form
+pub fn parse_num(x: &Val, span: &Span) -> RuntimeResult {
+    let text = text_arg(x, span)?;
+    if let Ok(num @ Val::Atom(Atom::Num(_))) = unformat(x, span) {
+        return Ok(num);
+    }
+    match text.trim().parse::<Num>() {
+        Ok(num) => Ok(num.into()),
+        Err(_) => rt_error(format!("Cannot parse {:?} as a number", text), span),
+    }
+}
+
+/// Format a number as text, with `digits` digits after the decimal point if given
+pub fn format_num(digits: Option<&Val>, x: &Val, span: &Span) -> RuntimeResult {
+    let num = match x {
+        Val::Atom(Atom::Num(num)) => *num,
+        x => return rt_error(format!("Cannot format {} as a number", x.type_name()), span),
+    };
+    let text = match digits {
+        None => match num {
+            Num::Int(i) => i.to_string(),
+            Num::Float(f) => f.to_string(),
+        },
+        Some(Val::Atom(Atom::Num(n))) if *n >= 0 => {
+            format!("{:.*}", i64::from(*n) as usize, f64::from(num))
+        }
+        Some(w) => {
+            return rt_error(
+                format!(
+                    "Digit count must be a natural number, but it is {}",
+                    w.type_name()
+                ),
+                span,
+            )
+        }
+    };
+    Ok(Array::string(text).into())
+}
+
+/// Get the display text of any value
+pub fn format(x: &Val) -> RuntimeResult {
+    Ok(Array::string(x.as_string()?).into())
+}
+
+/// Parse text as a number or array of numbers, using the same rules as literals
+pub fn unformat(x: &Val, span: &Span) -> RuntimeResult {
+    let text = text_arg(x, span)?;
+    let fail = || rt_error(format!("Cannot unformat {:?}", text), span);
+    let tokens = match lex_text(&text, &span.file) {
+        Ok(tokens) => tokens,
+        Err(_) => return fail(),
+    };
+    let mut stack: Vec<Vec<Val>> = vec![Vec::new()];
+    for token in tokens {
+        match token.tt {
+            TT::Num(num, _) => stack.last_mut().unwrap().push(num.into()),
+            TT::Whitespace | TT::Comma | TT::Newline => {}
+            TT::OpenAngle => stack.push(Vec::new()),
+            TT::CloseAngle if stack.len() > 1 => {
+                let items = stack.pop().unwrap();
+                stack
+                    .last_mut()
+                    .unwrap()
+                    .push(Array::concrete(items).into());
+            }
+            _ => return fail(),
+        }
+    }
+    if stack.len() > 1 {
+        return fail();
+    }
+    let mut items = stack.pop().unwrap();
+    match items.len() {
+        0 => fail(),
+        1 => Ok(items.pop().unwrap()),
+        _ => Ok(Array::concrete(items).into()),
+    }
+}
+
+#[test]
+fn number_text() {
+    use crate::eval::eval_test;
+    assert_eq!(eval_test("•ParseNum \" ‾2.5e2 \"").unwrap(), "‾250");
+    assert_eq!(eval_test("•ParseNum \"0x1F\"").unwrap(), "31");
+    assert_eq!(eval_test("•ParseNum \"‾∞\"").unwrap(), "‾∞");
+    assert!(eval_test("•ParseNum \"1 2\"").is_err());
+    assert_eq!(eval_test("•ParseNum \"-3\"").unwrap(), "‾3");
+    assert_eq!(eval_test("•ParseNum \" -2.5e-1\"").unwrap(), "‾0.25");
+    for x in ["‾3", "‾0.125", "1.5", "1e20", "‾∞"] {
+        assert_eq!(
+            eval_test(&format!("•ParseNum •FormatNum {x}")).unwrap(),
+            eval_test(x).unwrap()
+        );
+    }
+    assert_eq!(
+        eval_test("•Unformat \"1 ⟨2, 3⟩ 1_000\"").unwrap(),
+        "1\n⟨2 3⟩\n1000\n"
+    );
+    assert_eq!(
+        eval_test("•Unformat •Format ⟨1.5, ‾2⟩").unwrap(),
+        "⟨1.5 ‾2⟩"
+    );
+    assert_eq!(
+        eval_test("•Unformat \"1 x\"").unwrap_err(),
+        "Cannot unformat \"1 x\""
+    );
+}
+
+#[test]
+fn string_ops() {
+    use crate::eval::eval_test;
+    assert_eq!(
+        eval_test(r#"", " •Split "a, b,, c""#).unwrap(),
+        "\"a\"\n\"b,\"\n\"c\"\n"
+    );
+    assert_eq!(
+        eval_test(r#""-" •JoinWith ⟨"a", "bc", ""⟩"#).unwrap(),
+        "\"a-bc-\""
+    );
+    assert_eq!(eval_test(r#"•Trim "  a b  ""#).unwrap(), "\"a b\"");
+    assert_eq!(eval_test(r#""aa" •Find "aaab aa""#).unwrap(), "⟨0 5⟩");
+    assert_eq!(
+        eval_test(r#"⟨"ab", "x"⟩ •Replace "abcab""#).unwrap(),
+        "\"xcx\""
+    );
+    assert_eq!(eval_test(r#"•Upper "ᚠbé""#).unwrap(), "\"ᚠBÉ\"");
+    assert_eq!(eval_test(r#"•Lower "ÀB""#).unwrap(), "\"àb\"");
+    assert_eq!(eval_test("•FormatNum ‾1.5").unwrap(), "\"-1.5\"");
+    assert_eq!(eval_test("2 •FormatNum ‾1.256").unwrap(), "\"-1.26\"");
+    assert_eq!(eval_test("•FormatNum ‾∞").unwrap(), "\"-inf\"");
+}
@@ -0,0 +1,341 @@
+//! Evaluation of date and time operators
+//!
+//! Times are numbers of seconds since 1970-01-01T00:00:00Z, so arithmetic on
+//! them is ordinary arithmetic. All dates are in UTC.
+
+use std::{
+    cell::Cell,
+    time::{SystemTime, UNIX_EPOCH},
+};
+
+use crate::{
+    array::Array,
+    error::RuntimeResult,
+    eval::rt_error,
+    lex::{Ident, Span},
+    namespace::Namespace,
+    num::Num,
+    runtime::Runtime,
+    text::text_arg,
+    value::{Atom, Val},
+};
+
+const SECONDS_PER_DAY: i64 = 86400;
+const WEEKDAYS: [&str; 7] = ["Mon", "Tue", "Wed", "Thu", "Fri", "Sat", "Sun"];
+const MONTHS: [&str; 12] = [
+    "Jan", "Feb", "Mar", "Apr", "May", "Jun", "Jul", "Aug", "Sep", "Oct", "Nov", "Dec",
+];
+
+/// The clock read by `•Now`, which may be frozen at a fixed time
+#[derive(Default)]
+pub struct Clock {
+    frozen: Cell<Option<f64>>,
+}
+
+impl Runtime {
+    /// Get the current time, or the frozen time if the clock is frozen
+    pub fn now(&self) -> RuntimeResult {
+        Ok(match self.clock().frozen.get() {
+            Some(time) => Num::from(time).into(),
+            None => match SystemTime::now().duration_since(UNIX_EPOCH) {
+                Ok(elapsed) => Num::from(elapsed.as_secs_f64()).into(),
+                Err(e) => Num::from(-e.duration().as_secs_f64()).into(),
+            },
+        })
+    }
+    /// Freeze the clock at a time, or unfreeze it with an empty array
+    pub fn freeze_now(&self, x: Val, span: &Span) -> RuntimeResult {
+        match &x {
+            Val::Atom(Atom::Num(n)) => self.clock().frozen.set(Some(f64::from(*n))),
+            Val::Array(arr) if arr.len() == Some(0) => self.clock().frozen.set(None),
+            x => {
+                return rt_error(
+                    format!("The clock cannot be frozen at {}", x.type_name()),
+                    span,
+                )
+            }
+        }
+        Ok(x)
+    }
+}
+
+/// Convert a number of days to seconds
+pub fn days(x: &Val, span: &Span) -> RuntimeResult {
+    match x {
+        Val::Atom(Atom::Num(n)) => Ok((*n * Num::from(SECONDS_PER_DAY)).into()),
+        x => rt_error(
+            format!("Expected a number of days, but got {}", x.type_name()),
+            span,
+        ),
+    }
+}
+
+/// The components of a time
+struct DateTime {
+    year: i64,
+    month: i64,
+    day: i64,
+    hour: i64,
+    minute: i64,
+    second: i64,
+    millisecond: i64,
+    /// Days since the epoch
+    days: i64,
+}
+
+impl DateTime {
+    fn from_val(x: &Val, span: &Span) -> RuntimeResult<Self> {
+        let secs = match x {
+            Val::Atom(Atom::Num(n)) if !n.is_infinite() => f64::from(*n),
+            x => return rt_error(format!("Expected a time, but got {}", x.type_name()), span),
+        };
+        // Round before splitting so that a carry goes into the seconds
+        let millis = (secs * 1000.0).round() as i64;
+        let whole = millis.div_euclid(1000);
+        let days = whole.div_euclid(SECONDS_PER_DAY);
+        let of_day = whole.rem_euclid(SECONDS_PER_DAY);
+        let (year, month, day) = civil_from_days(days);
+        Ok(DateTime {
+            year,
+            month,
+            day,
+            hour: of_day / 3600,
+            minute: of_day / 60 % 60,
+            second: of_day % 60,
+            millisecond: millis.rem_euclid(1000),
+            days,
+        })
+    }
+    /// The ISO weekday, where Monday is 1 and Sunday is 7
+    fn weekday(&self) -> i64 {
+        (self.days + 3).rem_euclid(7) + 1
+    }
+    /// The day of the year, starting at 1
+    fn year_day(&self) -> i64 {
+        self.days - days_from_civil(self.year, 1, 1) + 1
+    }
+}
+
+/// Get the components of a time as a namespace
+pub fn date(x: &Val, span: &Span) -> RuntimeResult {
+    let dt = DateTime::from_val(x, span)?;
+    let second = if dt.millisecond == 0 {
+        Num::from(dt.second)
+    } else {
+        Num::from(dt.second as f64 + dt.millisecond as f64 / 1000.0)
+    };
+    let fields: [(&str, Num); 8] = [
+        ("year", dt.year.into()),
+        ("month", dt.month.into()),
+        ("day", dt.day.into()),
+        ("hour", dt.hour.into()),
+        ("minute", dt.minute.into()),
+        ("second", second),
+        ("weekday", dt.weekday().into()),
+        ("yearday", dt.year_day().into()),
+    ];
+    Ok(fields
+        .into_iter()
+        .map(|(name, n)| (Ident::from(name), Val::from(n)))
+        .collect::<Namespace>()
+        .into())
+}
+
+/// Format a time as ISO-8601 text, or with a pattern if given
+pub fn format_time(pattern: Option<&Val>, x: &Val, span: &Span) -> RuntimeResult {
+    let dt = DateTime::from_val(x, span)?;
+    let pattern = match pattern {
+        Some(pattern) => text_arg(pattern, span)?,
+        None if dt.millisecond == 0 => "%Y-%m-%dT%H:%M:%SZ".into(),
+        None => "%Y-%m-%dT%H:%M:%S.%fZ".into(),
+    };
+    let mut s = String::new();
+    let mut chars = pattern.chars();
+    while let Some(c) = chars.next() {
+        if c != '%' {
+            s.push(c);
+            continue;
+        }
+        match chars.next() {
+            Some('Y') => s.push_str(&format!("{:04}", dt.year)),
+            Some('m') => s.push_str(&format!("{:02}", dt.month)),
+            Some('d') => s.push_str(&format!("{:02}", dt.day)),
+            Some('H') => s.push_str(&format!("{:02}", dt.hour)),
+            Some('M') => s.push_str(&format!("{:02}", dt.minute)),
+            Some('S') => s.push_str(&format!("{:02}", dt.second)),
+            Some('f') => s.push_str(&format!("{:03}", dt.millisecond)),
+            Some('j') => s.push_str(&format!("{:03}", dt.year_day())),
+            Some('a') => s.push_str(WEEKDAYS[dt.weekday() as usize - 1]),
+            Some('b') => s.push_str(MONTHS[dt.month as usize - 1]),
+            Some('%') => s.push('%'),
+            Some(c) => return rt_error(format!("Unknown time format `%{}`", c), span),
+            None => return rt_error("Time format ends with `%`", span),
+        }
+    }
+    Ok(Array::string(s).into())
+}
+
+/// Parse ISO-8601 text as a time
+///
+/// Accepts `YYYY-MM-DD`, optionally followed by `THH:MM`, seconds,
+/// fractional seconds, and a `Z` or `±HH:MM` offset.
+pub fn parse_time(x: &Val, span: &Span) -> RuntimeResult {
+    let text = text_arg(x, span)?;
+    match parse_iso(text.trim()) {
+        Some(secs) if secs.fract() == 0.0 => Ok(Num::from(secs as i64).into()),
+        Some(secs) => Ok(Num::from(secs).into()),
+        None => rt_error(format!("Cannot parse {:?} as a time", text), span),
+    }
+}
+
+fn parse_iso(text: &str) -> Option<f64> {
+    let mut parser = IsoParser {
+        bytes: text.as_bytes(),
+        pos: 0,
+    };
+    let year = parser.number(4)?;
+    parser.expect(b'-')?;
+    let month = parser.number(2)?;
+    parser.expect(b'-')?;
+    let day = parser.number(2)?;
+    if !(1..=12).contains(&month) || day < 1 || day > days_in_month(year, month) {
+        return None;
+    }
+    let mut secs = (days_from_civil(year, month, day) * SECONDS_PER_DAY) as f64;
+    if parser
+        .expect(b'T')
+        .or_else(|| parser.expect(b' '))
+        .is_some()
+    {
+        let hour = parser.number(2)?;
+        parser.expect(b':')?;
+        let minute = parser.number(2)?;
+        let mut second = 0.0;
+        if parser.expect(b':').is_some() {
+            second = parser.number(2)? as f64;
+            if parser.expect(b'.').is_some() {
+                let start = parser.pos;
+                while parser.peek().is_some_and(|b| b.is_ascii_digit()) {
+                    parser.pos += 1;
+                }
+                second += format!("0.{}", &text[start..parser.pos])
+                    .parse::<f64>()
+                    .ok()?;
+            }
+        }
+        if hour > 23 || minute > 59 || second >= 60.0 {
+            return None;
+        }
+        secs += (hour * 3600 + minute * 60) as f64 + second;
+        match parser.peek() {
+            Some(b'Z') => parser.pos += 1,
+            Some(sign @ (b'+' | b'-')) => {
+                parser.pos += 1;
+                let hours = parser.number(2)?;
+                parser.expect(b':');
+                let minutes = parser.number(2)?;
+                let offset = (hours * 3600 + minutes * 60) as f64;
+                secs += if sign == b'+' { -offset } else { offset };
+            }
+            _ => {}
+        }
+    }
+    if parser.pos == text.len() {
+        Some(secs)
+    } else {
+        None
+    }
+}
+
+struct IsoParser<'a> {
+    bytes: &'a [u8],
+    pos: usize,
+}
+
+impl<'a> IsoParser<'a> {
+    fn peek(&self) -> Option<u8> {
+        self.bytes.get(self.pos).copied()
+    }
+    fn expect(&mut self, b: u8) -> Option<()> {
+        if self.peek() == Some(b) {
+            self.pos += 1;
+            Some(())
+        } else {
+            None
+        }
+    }
+    /// Parse a number with exactly `digits` digits
+    fn number(&mut self, digits: usize) -> Option<i64> {
+        let mut n = 0;
+        for _ in 0..digits {
+            let b = self.peek().filter(u8::is_ascii_digit)?;
+            n = n * 10 + (b - b'0') as i64;
+            self.pos += 1;
+        }
+        Some(n)
+    }
+}
+
+fn is_leap_year(year: i64) -> bool {
+    year % 4 == 0 && (year % 100 != 0 || year % 400 == 0)
+}
+
+fn days_in_month(year: i64, month: i64) -> i64 {
+    match month {
+        2 if is_leap_year(year) => 29,
+        2 => 28,
+        4 | 6 | 9 | 11 => 30,
+        _ => 31,
+    }
+}
+
+/// Days since the epoch of a date in the proleptic Gregorian calendar
+fn days_from_civil(year: i64, month: i64, day: i64) -> i64 {
+    let year = if month <= 2 { year - 1 } else { year };
+    let era = year.div_euclid(400);
+    let year_of_era = year - era * 400;
+    let day_of_year = (153 * ((month + 9) % 12) + 2) / 5 + day - 1;
+    let day_of_era = year_of_era * 365 + year_of_era / 4 - year_of_era / 100 + day_of_year;
+    era * 146097 + day_of_era - 719468
+}
+
+/// The date of a number of days since the epoch
+fn civil_from_days(days: i64) -> (i64, i64, i64) {
+    let days = days + 719468;
+    let era = days.div_euclid(146097);
+    let day_of_era = days - era * 146097;
+    let year_of_era =
+        (day_of_era - day_of_era / 1460 + day_of_era / 36524 - day_of_era / 146096) / 365;
+    let day_of_year = day_of_era - (365 * year_of_era + year_of_era / 4 - year_of_era / 100);
+    let mp = (5 * day_of_year + 2) / 153;
+    let day = day_of_year - (153 * mp + 2) / 5 + 1;
+    let month = if mp < 10 { mp + 3 } else { mp - 9 };
+    let year = year_of_era + era * 400 + if month <= 2 { 1 } else { 0 };
+    (year, month, day)
+}
+
+#[test]
+fn times() {
+    use crate::eval::eval_test;
+    let format_at = |t: &str| eval_test(&format!("•FreezeNow {}\n•FormatTime •Now 0", t));
+    assert_eq!(format_at("0").unwrap(), "\"1970-01-01T00:00:00Z\"");
+    assert_eq!(format_at("1.001").unwrap(), "\"1970-01-01T00:00:01.001Z\"");
+    assert_eq!(format_at("59.9996").unwrap(), "\"1970-01-01T00:01:00Z\"");
+    assert_eq!(format_at("‾0.5").unwrap(), "\"1969-12-31T23:59:59.500Z\"");
+    assert_eq!(
+        eval_test("•ParseTime \"2024-02-29T13:45:30+01:00\"").unwrap(),
+        "1709210730"
+    );
+    assert!(eval_test("•ParseTime \"2023-02-29\"").is_err());
+    let date = concat!(
+        "d ← •Date (•ParseTime \"1900-03-01\") + •Days 1\n",
+        "⟨d.year, d.month, d.day, d.weekday, d.yearday⟩"
+    );
+    assert_eq!(eval_test(date).unwrap(), "⟨1900 3 2 5 61⟩");
+    assert_eq!(
+        eval_test("\"%a %d %b %Y %j\" •FormatTime 0").unwrap(),
+        "\"Thu 01 Jan 1970 001\""
+    );
+    let frozen = "•FreezeNow 5\nt ← •Now 0\n•FreezeNow ⟨⟩\n⟨t, 1000000000 < •Now 0⟩";
+    assert_eq!(eval_test(frozen).unwrap(), "⟨5 1⟩");
+}
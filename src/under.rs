@@ -0,0 +1,247 @@
+//! Evaluation of the Under and Undo modifiers
+//!
+//! `ᛗFG x` applies `G` to `x`, applies `F` to the result, then undoes `G`.
+//! If `G` only rearranges or selects parts of `x`, it is undone by inserting
+//! the result back into `x`. Otherwise, `G`'s inverse is applied.
+//!
+//! `ᛠF x` applies `F`'s inverse to `x`.
+
+use crate::{
+    array::Array,
+    error::RuntimeResult,
+    eval::rt_error,
+    format::Format,
+    function::*,
+    lex::Span,
+    op::*,
+    runtime::Runtime,
+    value::{Atom, Val},
+};
+
+impl Runtime {
+    pub fn under(&self, f: Val, g: Val, w: Option<Val>, x: Val, span: &Span) -> RuntimeResult {
+        let g = match g {
+            Val::Atom(Atom::Function(g)) => g,
+            g => return rt_error(format!("{} cannot be undone", g.type_name()), span),
+        };
+        let gx = self.eval_un_function(g.clone(), x.clone(), span)?;
+        let fy = if let Some(w) = w {
+            let gw = self.eval_un_function(g.clone(), w, span)?;
+            self.eval_bin(f, gw, gx, span)?
+        } else {
+            self.eval_un(f, gx, span)?
+        };
+        if is_structural(&g) {
+            self.insert(&g, x, fy, span)
+        } else {
+            self.eval_un_function(g.inverse(span)?, fy, span)
+        }
+    }
+    pub fn undo(&self, f: Val, w: Option<Val>, x: Val, span: &Span) -> RuntimeResult {
+        let f = match f {
+            Val::Atom(Atom::Function(f)) => f,
+            f => return rt_error(format!("{} has no inverse", f.type_name()), span),
+        };
+        let f = if let Some(w) = w {
+            Fork {
+                left: w,
+                center: f,
+                right: RuneOp::Laguz.into(),
+            }
+            .into()
+        } else {
+            f
+        };
+        self.eval_un_function(f.inverse(span)?, x, span)
+    }
+    /// Insert a new value into the part of `x` that a structural function selects
+    fn insert(&self, g: &Function, x: Val, new: Val, span: &Span) -> RuntimeResult {
+        match g {
+            Function::Op(Op::Rune(RuneOp::Laguz)) => Ok(new),
+            Function::Op(Op::Rune(RuneOp::Jera)) => self.reverse(new, span),
+            Function::Op(Op::Rune(RuneOp::Perth)) => match x {
+                Val::Atom(_) => Ok(new),
+                x => replace(x, vec![(0i64.into(), new)], span),
+            },
+            Function::Fork(fork) => {
+                let w = self.eval_un(fork.left.clone(), x.clone(), span)?;
+                let y = self.eval_un_function(fork.right.clone(), x.clone(), span)?;
+                let inner = self.insert_bin(&fork.center, w, y, new, span)?;
+                self.insert(&fork.right, x, inner, span)
+            }
+            Function::Atop(atop) => {
+                let y = self.eval_un_function(atop.g.clone(), x.clone(), span)?;
+                let inner = self.insert(&atop.f, y, new, span)?;
+                self.insert(&atop.g, x, inner, span)
+            }
+            Function::UnMod(un_mod) => match (&un_mod.m, &un_mod.f) {
+                (UnMod::Rune(RuneUnMod::Berkanan), Val::Atom(Atom::Function(h))) => {
+                    match (x, new) {
+                        (Val::Array(x), Val::Array(new)) if x.len().is_some() => {
+                            if x.len() != new.len() {
+                                return rt_error(
+                                    "Each's result must keep its length to be undone",
+                                    span,
+                                );
+                            }
+                            Ok(Array::try_concrete(
+                                x.into_iter()
+                                    .zip(new)
+                                    .map(|(x, new)| self.insert(h, x?, new?, span)),
+                            )?
+                            .into())
+                        }
+                        (x, _) => {
+                            rt_error(format!("Each cannot be undone on {}", x.type_name()), span)
+                        }
+                    }
+                }
+                _ => not_structural(g, span),
+            },
+            g => not_structural(g, span),
+        }
+    }
+    /// Insert a new value into the part of `x` that a structural function with
+    /// a bound left argument selects
+    fn insert_bin(&self, g: &Function, w: Val, x: Val, new: Val, span: &Span) -> RuntimeResult {
+        Ok(match g {
+            Function::Op(Op::Rune(RuneOp::Laguz)) => new,
+            Function::Op(Op::Rune(RuneOp::Naudiz)) => {
+                let negative = is_negative(&w);
+                let rest = self.drop(w, x, span)?;
+                if negative {
+                    Array::JoinTo(rest.into(), new.into_array().into())
+                } else {
+                    Array::JoinTo(new.into_array().into(), rest.into())
+                }
+                .into()
+            }
+            Function::Op(Op::Rune(RuneOp::Gebo)) => {
+                let negative = is_negative(&w);
+                let rest = self.take(w, x, span)?;
+                if negative {
+                    Array::JoinTo(new.into_array().into(), rest.into())
+                } else {
+                    Array::JoinTo(rest.into(), new.into_array().into())
+                }
+                .into()
+            }
+            Function::Op(Op::Rune(RuneOp::Perth)) => replace(x, vec![(w, new)], span)?,
+            Function::Op(Op::Rune(RuneOp::Ansuz)) => match (w, new) {
+                (w @ Val::Atom(_), new) => replace(x, vec![(w, new)], span)?,
+                (Val::Array(w), Val::Array(new)) if w.len().is_some() && w.len() == new.len() => {
+                    let replacements = w
+                        .into_iter()
+                        .zip(new)
+                        .map(|(i, new)| Ok((i?, new?)))
+                        .collect::<RuntimeResult<_>>()?;
+                    replace(x, replacements, span)?
+                }
+                _ => return rt_error("Select's result must keep its length to be undone", span),
+            },
+            g => return not_structural(g, span),
+        })
+    }
+}
+
+/// Check if a function only rearranges or selects parts of its argument
+fn is_structural(g: &Function) -> bool {
+    match g {
+        Function::Op(Op::Rune(RuneOp::Laguz | RuneOp::Jera | RuneOp::Perth)) => true,
+        Function::Fork(fork) => {
+            matches!(
+                fork.center,
+                Function::Op(Op::Rune(
+                    RuneOp::Laguz | RuneOp::Naudiz | RuneOp::Gebo | RuneOp::Perth | RuneOp::Ansuz
+                ))
+            ) && is_structural(&fork.right)
+        }
+        Function::Atop(atop) => is_structural(&atop.f) && is_structural(&atop.g),
+        Function::UnMod(un_mod) => match (&un_mod.m, &un_mod.f) {
+            (UnMod::Rune(RuneUnMod::Berkanan), Val::Atom(Atom::Function(h))) => is_structural(h),
+            _ => false,
+        },
+        _ => false,
+    }
+}
+
+fn not_structural<T>(g: &Function, span: &Span) -> RuntimeResult<T> {
+    rt_error(format!("{} cannot be undone", g.as_string()?), span)
+}
+
+fn is_negative(n: &Val) -> bool {
+    matches!(n, Val::Atom(Atom::Num(n)) if *n < 0)
+}
+
+/// Replace items in an array
+///
+/// Each index may be a number or an array of numbers that index successively
+/// deeper into the array.
+fn replace(x: Val, replacements: Vec<(Val, Val)>, span: &Span) -> RuntimeResult {
+    let arr = match x {
+        Val::Array(arr) if arr.len().is_some() => arr,
+        Val::Array(_) => return rt_error("Unbounded arrays cannot be modified", span),
+        Val::Atom(atom) => {
+            return rt_error(format!("{} cannot be indexed", atom.type_name()), span)
+        }
+    };
+    let mut items = arr.into_vec()?;
+    for (index, new) in replacements {
+        let (i, rest) = match index {
+            Val::Atom(Atom::Num(i)) => (i64::from(i), None),
+            Val::Array(path) => {
+                let mut path = path.into_vec()?;
+                if path.is_empty() {
+                    return Ok(new);
+                }
+                let i = match path.remove(0) {
+                    Val::Atom(Atom::Num(i)) => i64::from(i),
+                    val => {
+                        return rt_error(
+                            format!("{} cannot be used as an index", val.type_name()),
+                            span,
+                        )
+                    }
+                };
+                (i, Some(Array::concrete(path)))
+            }
+            Val::Atom(atom) => {
+                return rt_error(
+                    format!("{} cannot be used as an index", atom.type_name()),
+                    span,
+                )
+            }
+        };
+        let len = items.len();
+        let resolved = if i >= 0 { i } else { len as i64 + i };
+        if resolved < 0 || resolved as usize >= len {
+            return rt_error(
+                format!("Index {} is out of bounds of array length {}", i, len),
+                span,
+            );
+        }
+        let item = &mut items[resolved as usize];
+        *item = match rest {
+            Some(rest) => replace(item.clone(), vec![(rest.into(), new)], span)?,
+            None => new,
+        };
+    }
+    Ok(Array::concrete(items).into())
+}
+
+#[test]
+fn under_negative_take_and_drop() {
+    use crate::eval::eval_test;
+    assert_eq!(
+        eval_test("ᛗ(1᛭ᛚ)(‾2ᚾᛚ) ⟨1, 2, 3, 4, 5⟩").unwrap(),
+        "⟨1 2 3 5 6⟩"
+    );
+    assert_eq!(
+        eval_test("ᛗ(1᛭ᛚ)(‾2ᚷᛚ) ⟨1, 2, 3, 4, 5⟩").unwrap(),
+        "⟨2 3 4 4 5⟩"
+    );
+    assert_eq!(
+        eval_test("ᛗ(1᛭ᛚ)(2ᚾᛚ) ⟨1, 2, 3, 4, 5⟩").unwrap(),
+        "⟨2 3 3 4 5⟩"
+    );
+}
@@ -1,4 +1,4 @@
-use std::fmt;
+use std::{fmt, rc::Rc};
 
 use crate::{
     array::Array,
@@ -6,6 +6,8 @@ use crate::{
     format::{Format, Formatter},
     function::*,
     lex::Span,
+    map::Map,
+    namespace::Namespace,
     num::Num,
     op::*,
     pervade::LazyPervade,
@@ -18,6 +20,8 @@ pub enum Atom {
     Function(Function),
     UnMod(UnMod),
     BinMod(BinMod),
+    Namespace(Rc<Namespace>),
+    Map(Rc<Map>),
 }
 
 impl Atom {
@@ -28,6 +32,8 @@ impl Atom {
             Atom::Function(f) => f.type_name(),
             Atom::UnMod(_) => "unary modifier",
             Atom::BinMod(_) => "binary modifier",
+            Atom::Namespace(_) => "namespace",
+            Atom::Map(_) => "map",
         }
     }
 }
@@ -83,6 +89,12 @@ impl From<BinModded> for Atom {
     }
 }
 
+impl From<Map> for Atom {
+    fn from(map: Map) -> Self {
+        Atom::Map(Rc::new(map))
+    }
+}
+
 impl From<Function> for Atom {
     fn from(f: Function) -> Self {
         Atom::Function(f)
@@ -97,6 +109,8 @@ impl fmt::Debug for Atom {
             Atom::Function(fun) => fun.fmt(f),
             Atom::UnMod(m) => m.fmt(f),
             Atom::BinMod(m) => m.fmt(f),
+            Atom::Namespace(ns) => ns.fmt(f),
+            Atom::Map(map) => map.fmt(f),
         }
     }
 }
@@ -109,6 +123,8 @@ impl Format for Atom {
             Atom::Function(fun) => fun.format(f)?,
             Atom::UnMod(m) => f.display(m),
             Atom::BinMod(m) => f.display(m),
+            Atom::Namespace(ns) => ns.format(f)?,
+            Atom::Map(map) => map.format(f)?,
         }
         Ok(())
     }
@@ -122,9 +138,9 @@ pub enum Val {
 
 fn _val_size() {
     use std::mem::transmute;
-    let _: [u8; 48] = unsafe { transmute(Atom::from(1i64)) };
+    let _: [u8; 24] = unsafe { transmute(Atom::from(1i64)) };
     let _: [u8; 40] = unsafe { transmute(Array::string("")) };
-    let _: [u8; 56] = unsafe { transmute(Val::from(1i64)) };
+    let _: [u8; 40] = unsafe { transmute(Val::from(1i64)) };
 }
 
 impl Val {
@@ -142,11 +158,30 @@ impl Val {
     }
     pub fn matches(&self, other: &Self) -> RuntimeResult<bool> {
         match (self, other) {
+            (Val::Atom(Atom::Namespace(a)), Val::Atom(Atom::Namespace(b))) => a.matches(b),
+            (Val::Atom(Atom::Map(a)), Val::Atom(Atom::Map(b))) => a.matches(b),
             (Val::Atom(a), Val::Atom(b)) => Ok(a == b),
             (Val::Array(a), Val::Array(b)) => a.matches(b),
             _ => Ok(false),
         }
     }
+    /// Get the text of a finite array of characters
+    pub fn as_text(&self) -> RuntimeResult<Option<String>> {
+        let arr = match self {
+            Val::Array(Array::AsciiString(s)) => return Ok(Some(s.to_string())),
+            Val::Array(Array::Utf32String(s)) => return Ok(Some(s.iter().collect())),
+            Val::Array(arr) if arr.len().is_some() => arr,
+            _ => return Ok(None),
+        };
+        let mut s = String::new();
+        for val in arr.iter() {
+            match val?.as_ref() {
+                Val::Atom(Atom::Char(c)) => s.push(*c),
+                _ => return Ok(None),
+            }
+        }
+        Ok(Some(s))
+    }
     pub fn limited_depth(&self) -> RuntimeResult<usize> {
         match self {
             Val::Atom(_) => Ok(0),